@@ -0,0 +1,43 @@
+use config::{reload_on_change, Config, FromConfig};
+use std::error::Error;
+use std::time::Duration;
+use sync_utils::Watch;
+
+#[derive(Debug, Clone, Config)]
+struct Config {
+	#[env_file = "BAR_FILE"]
+	bar: String,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+	let config = match Config::parse() {
+		Ok(conf) => conf,
+		Err(err) => return print_error(&err),
+	};
+
+	let watch = Watch::new();
+	watch.update(config);
+
+	let printer = async {
+		loop {
+			if let Some(conf) = watch.latest() {
+				println!("{}", conf.bar);
+			}
+			tokio::time::sleep(Duration::from_secs(1)).await;
+		}
+	};
+
+	let reloader = reload_on_change(&watch, Duration::from_secs(1), |err| print_error(&err));
+
+	tokio::join!(printer, reloader);
+}
+
+fn print_error(error: &dyn Error) {
+	println!("{}", error);
+
+	if let Some(source) = error.source() {
+		println!(" due to ");
+		print_error(source);
+	}
+}
@@ -1,3 +1,4 @@
+use error::ExitCodeError;
 use std::error::Error;
 use std::ffi::OsString;
 use thiserror::Error;
@@ -29,4 +30,18 @@ pub enum ArgumentParseError {
 		path: String,
 		source: Box<dyn Error>,
 	},
+	#[error("argument {name} for field {field} violates rule {rule}")]
+	Invalid {
+		name: &'static str,
+		field: &'static str,
+		rule: &'static str,
+		source: Box<dyn Error>,
+	},
+}
+
+/// Exits with `EX_CONFIG` of sysexits.h, as restarting won't help until the configuration is fixed.
+impl ExitCodeError for ArgumentParseError {
+	fn exit_code(&self) -> i32 {
+		78
+	}
 }
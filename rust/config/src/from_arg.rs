@@ -6,6 +6,8 @@ use std::num::{
 	NonZeroU32, NonZeroU64, NonZeroU8,
 };
 use std::path::PathBuf;
+use std::time::Duration;
+use thiserror::Error;
 
 pub trait FromArg: Sized {
 	type Error: Error;
@@ -41,3 +43,66 @@ impl_from_arg_with_parse!(
 	SocketAddrV6,
 	SocketAddr
 );
+
+#[derive(Debug, Error)]
+#[error("invalid duration {0:?}, expected a whole number of ms, s, m, h or d, e.g. 90s")]
+pub struct ParseDurationError(String);
+
+/// Parses a whole number followed by a unit, e.g. `500ms` or `1h`. A bare number is taken as
+/// seconds.
+impl FromArg for Duration {
+	type Error = ParseDurationError;
+
+	fn parse_arg(argument: &str) -> Result<Self, Self::Error> {
+		let invalid = || ParseDurationError(argument.to_string());
+
+		let unit_start = argument
+			.find(|c: char| !c.is_ascii_digit())
+			.unwrap_or(argument.len());
+		let (value, unit) = argument.split_at(unit_start);
+		let value = value.parse::<u64>().map_err(|_| invalid())?;
+
+		let seconds_per_unit = match unit {
+			"ms" => return Ok(Duration::from_millis(value)),
+			"" | "s" => 1,
+			"m" => 60,
+			"h" => 60 * 60,
+			"d" => 24 * 60 * 60,
+			_ => return Err(invalid()),
+		};
+
+		value
+			.checked_mul(seconds_per_unit)
+			.map(Duration::from_secs)
+			.ok_or_else(invalid)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_durations() {
+		// Arrange
+		let arguments = ["500ms", "90", "90s", "5m", "1h", "1d"];
+
+		// Act
+		let durations = arguments.map(Duration::parse_arg).map(Result::unwrap);
+
+		// Assert
+		assert_eq!(
+			durations,
+			[
+				Duration::from_millis(500),
+				Duration::from_secs(90),
+				Duration::from_secs(90),
+				Duration::from_secs(5 * 60),
+				Duration::from_secs(60 * 60),
+				Duration::from_secs(24 * 60 * 60),
+			]
+		);
+		assert!(Duration::parse_arg("1.5h").is_err());
+		assert!(Duration::parse_arg("h").is_err());
+	}
+}
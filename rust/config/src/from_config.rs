@@ -1,5 +1,10 @@
 use crate::argument_parse_error::ArgumentParseError;
+use std::path::PathBuf;
 
 pub trait FromConfig: Sized {
 	fn parse() -> Result<Self, ArgumentParseError>;
+
+	fn files() -> Vec<PathBuf> {
+		Vec::new()
+	}
 }
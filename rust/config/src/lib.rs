@@ -1,9 +1,86 @@
+extern crate self as config;
+
 mod from_config;
 
 mod argument_parse_error;
 mod from_arg;
+#[cfg(feature = "reload")]
+mod reload;
+pub mod validate;
 
 pub use argument_parse_error::ArgumentParseError;
 pub use config_macro::Config;
-pub use from_arg::FromArg;
+pub use from_arg::{FromArg, ParseDurationError};
 pub use from_config::FromConfig;
+#[cfg(feature = "reload")]
+pub use reload::*;
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Debug, Config)]
+	struct Defaulted {
+		#[env = "CONFIG_TEST_DEFAULTED_PORT"]
+		#[default = "8080"]
+		port: u32,
+	}
+
+	#[derive(Debug, Config)]
+	struct Overridden {
+		#[env = "CONFIG_TEST_OVERRIDDEN_PORT"]
+		#[default = "8080"]
+		port: u32,
+	}
+
+	#[derive(Debug, Config)]
+	struct InvalidDefault {
+		#[allow(unused)]
+		#[env = "CONFIG_TEST_INVALID_DEFAULT_PORT"]
+		#[default = "0"]
+		#[validate(range(1..=65535))]
+		port: u32,
+	}
+
+	#[test]
+	fn default_is_used_when_env_var_is_missing() {
+		// Arrange
+		std::env::remove_var("CONFIG_TEST_DEFAULTED_PORT");
+
+		// Act
+		let config = Defaulted::parse();
+
+		// Assert
+		assert_eq!(config.unwrap().port, 8080);
+	}
+
+	#[test]
+	fn env_var_overrides_default() {
+		// Arrange
+		std::env::set_var("CONFIG_TEST_OVERRIDDEN_PORT", "9090");
+
+		// Act
+		let config = Overridden::parse();
+
+		// Assert
+		assert_eq!(config.unwrap().port, 9090);
+	}
+
+	#[test]
+	fn default_is_validated() {
+		// Arrange
+		std::env::remove_var("CONFIG_TEST_INVALID_DEFAULT_PORT");
+
+		// Act
+		let config = InvalidDefault::parse();
+
+		// Assert
+		assert!(matches!(
+			config,
+			Err(ArgumentParseError::Invalid {
+				name: "CONFIG_TEST_INVALID_DEFAULT_PORT",
+				..
+			})
+		));
+	}
+}
@@ -0,0 +1,173 @@
+use crate::{ArgumentParseError, FromConfig};
+use std::path::PathBuf;
+use std::time::Duration;
+use sync_utils::Watch;
+
+/// Re-parses `C` whenever one of its file backed arguments changes and publishes the result to
+/// `config`.
+///
+/// The files are polled every `interval` rather than watched, as mounted Kubernetes secrets are
+/// rotated by swapping symlinks which file system notifications does not reliably report. Should
+/// the changed files not parse, the previously published value is kept and the error is handed to
+/// `on_error`.
+pub async fn reload_on_change<C: FromConfig>(
+	config: &Watch<C>,
+	interval: Duration,
+	mut on_error: impl FnMut(ArgumentParseError),
+) {
+	let mut snapshot = Snapshot::take(C::files());
+
+	loop {
+		tokio::time::sleep(interval).await;
+
+		let current_snapshot = Snapshot::take(C::files());
+
+		if current_snapshot == snapshot {
+			continue;
+		}
+
+		snapshot = current_snapshot;
+
+		match C::parse() {
+			Ok(new_config) => config.update(new_config),
+			Err(err) => on_error(err),
+		}
+	}
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Snapshot(Vec<(PathBuf, Option<Vec<u8>>)>);
+
+impl Snapshot {
+	fn take(files: Vec<PathBuf>) -> Snapshot {
+		let contents = files
+			.into_iter()
+			.map(|path| {
+				let content = std::fs::read(&path).ok();
+				(path, content)
+			})
+			.collect();
+
+		Snapshot(contents)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::error::Error;
+	use std::sync::{Arc, Mutex};
+
+	#[derive(Debug, Clone, PartialEq)]
+	struct TestConfig<const ID: u8> {
+		value: u32,
+	}
+
+	impl<const ID: u8> TestConfig<ID> {
+		fn path() -> PathBuf {
+			std::env::temp_dir().join(format!("config-reload-test-{}-{ID}", std::process::id()))
+		}
+
+		fn write(content: &str) {
+			std::fs::write(Self::path(), content).expect("temp dir should be writable");
+		}
+
+		/// Writes the file, which is removed once the returned guard is dropped.
+		fn create(content: &str) -> RemoveOnDrop {
+			Self::write(content);
+			RemoveOnDrop(Self::path())
+		}
+	}
+
+	/// Removes the file at its path when dropped, even if the test panicked.
+	struct RemoveOnDrop(PathBuf);
+
+	impl Drop for RemoveOnDrop {
+		fn drop(&mut self) {
+			let _ = std::fs::remove_file(&self.0);
+		}
+	}
+
+	impl<const ID: u8> FromConfig for TestConfig<ID> {
+		fn parse() -> Result<Self, ArgumentParseError> {
+			let path = Self::path();
+			let content =
+				std::fs::read_to_string(&path).map_err(|err| ArgumentParseError::NotReadable {
+					name: "TEST_FILE",
+					path: path.display().to_string(),
+					source: Box::new(err) as Box<dyn Error>,
+				})?;
+
+			let value = content
+				.trim()
+				.parse()
+				.map_err(|err| ArgumentParseError::NotParseable {
+					name: "TEST_FILE",
+					ty: "u32",
+					source: Box::new(err) as Box<dyn Error>,
+				})?;
+
+			Ok(TestConfig { value })
+		}
+
+		fn files() -> Vec<PathBuf> {
+			vec![Self::path()]
+		}
+	}
+
+	fn spawn_reloader<C: FromConfig + Clone + Send + Sync + 'static>(
+		config: &Watch<C>,
+	) -> Arc<Mutex<Vec<String>>> {
+		let errors = Arc::new(Mutex::new(Vec::new()));
+
+		tokio::spawn({
+			let config = config.clone();
+			let errors = errors.clone();
+
+			async move {
+				reload_on_change(&config, Duration::from_secs(1), |err| {
+					errors.lock().unwrap().push(err.to_string())
+				})
+				.await
+			}
+		});
+
+		errors
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn publishes_changed_file() {
+		// Arrange
+		let _file = TestConfig::<0>::create("1");
+		let config = Watch::new();
+		config.update(TestConfig::<0>::parse().unwrap());
+		let errors = spawn_reloader(&config);
+		tokio::time::sleep(Duration::from_millis(100)).await;
+
+		// Act
+		TestConfig::<0>::write("2");
+		tokio::time::sleep(Duration::from_secs(2)).await;
+
+		// Assert
+		assert_eq!(config.latest(), Some(TestConfig { value: 2 }));
+		assert!(errors.lock().unwrap().is_empty());
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn keeps_previous_value_when_changed_file_is_invalid() {
+		// Arrange
+		let _file = TestConfig::<1>::create("1");
+		let config = Watch::new();
+		config.update(TestConfig::<1>::parse().unwrap());
+		let errors = spawn_reloader(&config);
+		tokio::time::sleep(Duration::from_millis(100)).await;
+
+		// Act
+		TestConfig::<1>::write("not a number");
+		tokio::time::sleep(Duration::from_secs(2)).await;
+
+		// Assert
+		assert_eq!(config.latest(), Some(TestConfig { value: 1 }));
+		assert_eq!(errors.lock().unwrap().len(), 1);
+	}
+}
@@ -0,0 +1,153 @@
+use std::ffi::{OsStr, OsString};
+use std::fmt::Debug;
+use std::ops::RangeBounds;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+pub use regex::Regex;
+
+/// Leaves out the rejected value, which may well be a secret read from a file.
+#[derive(Debug, Error)]
+pub enum ValidationError {
+	#[error("value is not within range {range}")]
+	OutOfRange { range: String },
+	#[error("value is empty")]
+	Empty,
+	#[error("value does not match pattern {pattern}")]
+	NoMatch { pattern: String },
+}
+
+pub fn range<T: PartialOrd, R: RangeBounds<T> + Debug>(
+	value: &T,
+	range: R,
+) -> Result<(), ValidationError> {
+	if range.contains(value) {
+		return Ok(());
+	}
+
+	Err(ValidationError::OutOfRange {
+		range: format!("{range:?}"),
+	})
+}
+
+pub fn non_empty<T: ?Sized + IsEmpty>(value: &T) -> Result<(), ValidationError> {
+	if value.is_empty() {
+		return Err(ValidationError::Empty);
+	}
+
+	Ok(())
+}
+
+pub fn regex<T: ?Sized + AsRef<str>>(value: &T, regex: &Regex) -> Result<(), ValidationError> {
+	let value = value.as_ref();
+
+	if regex.is_match(value) {
+		return Ok(());
+	}
+
+	Err(ValidationError::NoMatch {
+		pattern: regex.to_string(),
+	})
+}
+
+pub trait IsEmpty {
+	fn is_empty(&self) -> bool;
+}
+
+macro_rules! impl_is_empty {
+	($( $t:ty ),*) => {
+		$(
+			impl IsEmpty for $t {
+				fn is_empty(&self) -> bool {
+					<$t>::is_empty(self)
+				}
+			}
+		)*
+	};
+}
+
+impl_is_empty!(str, String, OsStr);
+
+impl IsEmpty for OsString {
+	fn is_empty(&self) -> bool {
+		self.as_os_str().is_empty()
+	}
+}
+
+impl IsEmpty for Path {
+	fn is_empty(&self) -> bool {
+		self.as_os_str().is_empty()
+	}
+}
+
+impl IsEmpty for PathBuf {
+	fn is_empty(&self) -> bool {
+		self.as_os_str().is_empty()
+	}
+}
+
+impl<T> IsEmpty for Vec<T> {
+	fn is_empty(&self) -> bool {
+		Vec::is_empty(self)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn range_accepts_contained_value() {
+		// Arrange
+		let port: u16 = 8080;
+
+		// Act
+		let res = range(&port, 1..=65535);
+
+		// Assert
+		assert!(res.is_ok());
+	}
+
+	#[test]
+	fn range_rejects_value_outside() {
+		// Arrange
+		let port: u16 = 0;
+
+		// Act
+		let res = range(&port, 1..=65535);
+
+		// Assert
+		assert_eq!(
+			res.unwrap_err().to_string(),
+			"value is not within range 1..=65535"
+		);
+	}
+
+	#[test]
+	fn non_empty_rejects_empty_string() {
+		// Arrange
+		let value = String::new();
+
+		// Act
+		let res = non_empty(&value);
+
+		// Assert
+		assert!(matches!(res, Err(ValidationError::Empty)));
+	}
+
+	#[test]
+	fn regex_rejects_mismatch() {
+		// Arrange
+		let pattern = Regex::new("^https://").unwrap();
+		let value = String::from("http://secret@example.com");
+
+		// Act
+		let res = regex(&value, &pattern);
+
+		// Assert
+		assert_eq!(
+			res.unwrap_err().to_string(),
+			"value does not match pattern ^https://"
+		);
+	}
+}
@@ -1,9 +1,15 @@
 use proc_macro2::TokenStream;
 use quote::{quote, quote_spanned};
 use syn::spanned::Spanned;
-use syn::{parse_macro_input, Attribute, Data, DeriveInput, Expr, Fields, Meta};
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Expr, Fields, LitStr, Meta, Path};
 
-#[proc_macro_derive(Config, attributes(env, env_file))]
+/// Implements `FromConfig` for a struct, parsing each field with `FromArg` from:
+/// - `#[env = "NAME"]`: the env variable `NAME`, or the value of `#[default = "..."]` if it is
+///   not set, parsed as if it were the env variable.
+/// - `#[env_file = "NAME"]`: the contents of the file at the path given by the env variable `NAME`.
+///
+/// `#[validate(...)]` checks the parsed value, e.g. `#[validate(range(1..=65535))]`.
+#[proc_macro_derive(Config, attributes(env, env_file, default, validate))]
 pub fn derive_config(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 	let input = parse_macro_input!(input as DeriveInput);
 	inner_derive_config(input).into()
@@ -20,6 +26,7 @@ fn inner_derive_config(input: DeriveInput) -> TokenStream {
 	};
 
 	let mut field_constructors = Vec::new();
+	let mut file_vars = Vec::new();
 	for field in fields.named {
 		let ident = field
 			.ident
@@ -38,7 +45,21 @@ fn inner_derive_config(input: DeriveInput) -> TokenStream {
 			Ok(v) => v,
 		};
 
-		let field_constructor = match (env_var, env_file_var) {
+		let default = match find_name_value_attribute_value(&field.attrs, "default") {
+			Err(err) => return err,
+			Ok(v) => v.map(|default| {
+				quote! {
+					.or_else(|| ::std::option::Option::Some(::std::ffi::OsString::from(#default)))
+				}
+			}),
+		};
+
+		let validations = match find_validations(&field.attrs) {
+			Err(err) => return err.to_compile_error(),
+			Ok(v) => v,
+		};
+
+		let (name, parser) = match (env_var, env_file_var) {
 			(None, None) => {
 				return quote_spanned!( field.span()=> compile_error!("no env nor env_file attribute for field");)
 			}
@@ -47,8 +68,9 @@ fn inner_derive_config(input: DeriveInput) -> TokenStream {
 				return quote_spanned!( field.span()=> compile_error!("both env and env_file attribute for field");)
 			}
 
-			(Some(env_var), None) => quote! {
-				#ident: ::std::env::var_os(#env_var)
+			(Some(env_var), None) => (env_var, quote! {
+				::std::env::var_os(#env_var)
+					#default
 					.ok_or(::config::ArgumentParseError::Missing { name: #env_var })
 					.and_then(|arg| {
 						arg.into_string()
@@ -65,58 +87,87 @@ fn inner_derive_config(input: DeriveInput) -> TokenStream {
 								source: ::std::boxed::Box::new(err) as ::std::boxed::Box<dyn ::std::error::Error>,
 							}
 						})
-					})?
-			},
-
-			(None, Some(env_file_var)) => quote! {
-				#ident: ::std::env::var_os(#env_file_var)
-					.ok_or(::config::ArgumentParseError::Missing { name: #env_file_var })
-					.and_then(|arg| {
-						arg.into_string()
-							.map_err(|actual| ::config::ArgumentParseError::NotUnicode {
-								name: #env_file_var,
-								actual,
-							})
 					})
-					.and_then(|path| {
-						let file = ::std::fs::File::open(&path).map_err(|err| {
-							::config::ArgumentParseError::NotAccessible {
-								name: #env_file_var,
-								path: path.clone(),
-								source: ::std::boxed::Box::new(err)
-									as ::std::boxed::Box<dyn ::std::error::Error>,
-							}
-						})?;
-
-						let res = ::std::io::read_to_string(file).map_err(|err| {
-							::config::ArgumentParseError::NotReadable {
-								name: #env_file_var,
-								path,
-								source: ::std::boxed::Box::new(err)
-									as ::std::boxed::Box<dyn ::std::error::Error>,
-							}
-						})?;
+			}),
 
-						Ok(res)
-					})
-					.and_then(|arg| {
-						<#ty as ::config::FromArg>::parse_arg(arg.as_str().trim()).map_err(|err| {
-							::config::ArgumentParseError::NotParseable {
-								name: #env_file_var,
-								ty: ::std::any::type_name::<#ty>(),
-								source: ::std::boxed::Box::new(err)
-									as ::std::boxed::Box<dyn ::std::error::Error>,
-							}
+			(None, Some(_)) if default.is_some() => {
+				return quote_spanned!( field.span()=> compile_error!("default attribute is only supported along with env attribute");)
+			}
+
+			(None, Some(env_file_var)) => {
+				file_vars.push(env_file_var.clone());
+
+				let parser = quote! {
+					::std::env::var_os(#env_file_var)
+						.ok_or(::config::ArgumentParseError::Missing { name: #env_file_var })
+						.and_then(|arg| {
+							arg.into_string()
+								.map_err(|actual| ::config::ArgumentParseError::NotUnicode {
+									name: #env_file_var,
+									actual,
+								})
 						})
-					})?
-			},
+						.and_then(|path| {
+							let file = ::std::fs::File::open(&path).map_err(|err| {
+								::config::ArgumentParseError::NotAccessible {
+									name: #env_file_var,
+									path: path.clone(),
+									source: ::std::boxed::Box::new(err)
+										as ::std::boxed::Box<dyn ::std::error::Error>,
+								}
+							})?;
+
+							let res = ::std::io::read_to_string(file).map_err(|err| {
+								::config::ArgumentParseError::NotReadable {
+									name: #env_file_var,
+									path,
+									source: ::std::boxed::Box::new(err)
+										as ::std::boxed::Box<dyn ::std::error::Error>,
+								}
+							})?;
+
+							Ok(res)
+						})
+						.and_then(|arg| {
+							<#ty as ::config::FromArg>::parse_arg(arg.as_str().trim()).map_err(|err| {
+								::config::ArgumentParseError::NotParseable {
+									name: #env_file_var,
+									ty: ::std::any::type_name::<#ty>(),
+									source: ::std::boxed::Box::new(err)
+										as ::std::boxed::Box<dyn ::std::error::Error>,
+								}
+							})
+						})
+				};
+
+				(env_file_var, parser)
+			}
 		};
 
-		field_constructors.push(field_constructor);
+		let field_name = ident.to_string();
+		let validators = validations
+			.iter()
+			.map(|validation| validation.to_tokens(name, &field_name));
+
+		field_constructors.push(quote! {
+			#ident: #parser #(#validators)*?
+		});
 	}
 
 	let name = input.ident;
 
+	let files = (!file_vars.is_empty()).then(|| {
+		quote! {
+			fn files() -> ::std::vec::Vec<::std::path::PathBuf> {
+				[#(#file_vars),*]
+					.into_iter()
+					.filter_map(::std::env::var_os)
+					.map(::std::path::PathBuf::from)
+					.collect()
+			}
+		}
+	});
+
 	quote! {
 		impl ::config::FromConfig for #name {
 			fn parse() -> Result<Self, ::config::ArgumentParseError> {
@@ -124,6 +175,8 @@ fn inner_derive_config(input: DeriveInput) -> TokenStream {
 					#(#field_constructors,)*
 				})
 			}
+
+			#files
 		}
 	}
 }
@@ -162,6 +215,115 @@ fn find_name_value_attribute_value<'a>(
 	Ok(res)
 }
 
+enum Validation {
+	Range(Expr),
+	NonEmpty,
+	Regex(LitStr),
+	With(Path),
+}
+
+impl Validation {
+	fn rule(&self) -> String {
+		let rule = match self {
+			Validation::Range(range) => format!("range({})", quote!(#range)),
+			Validation::NonEmpty => String::from("non_empty"),
+			Validation::Regex(pattern) => format!("regex = {:?}", pattern.value()),
+			Validation::With(path) => format!("with = {}", quote!(#path)),
+		};
+
+		match self {
+			Validation::Regex(_) => rule,
+			_ => rule.split_whitespace().collect(),
+		}
+	}
+
+	fn to_tokens(&self, name: &Expr, field: &str) -> TokenStream {
+		let check = match self {
+			Validation::Range(range) => quote! {
+				::config::validate::range(&value, #range)
+			},
+			Validation::NonEmpty => quote! {
+				::config::validate::non_empty(&value)
+			},
+			Validation::Regex(pattern) => quote! {
+				{
+					static REGEX: ::std::sync::OnceLock<::config::validate::Regex> =
+						::std::sync::OnceLock::new();
+					let regex = REGEX.get_or_init(|| {
+						::config::validate::Regex::new(#pattern)
+							.expect("pattern should have been checked when deriving Config")
+					});
+					::config::validate::regex(&value, regex)
+				}
+			},
+			Validation::With(path) => quote! {
+				#path(&value)
+			},
+		};
+
+		let rule = self.rule();
+
+		quote! {
+			.and_then(|value| {
+				let validity = #check;
+				validity.map(|()| value).map_err(|err| {
+					::config::ArgumentParseError::Invalid {
+						name: #name,
+						field: #field,
+						rule: #rule,
+						source: ::std::boxed::Box::new(err)
+							as ::std::boxed::Box<dyn ::std::error::Error>,
+					}
+				})
+			})
+		}
+	}
+}
+
+fn find_validations(attributes: &[Attribute]) -> syn::Result<Vec<Validation>> {
+	let mut res = Vec::new();
+	for attribute in attributes {
+		if !attribute.path().is_ident("validate") {
+			continue;
+		}
+
+		attribute.parse_nested_meta(|meta| {
+			if meta.path.is_ident("range") {
+				let content;
+				syn::parenthesized!(content in meta.input);
+				res.push(Validation::Range(content.parse()?));
+				return Ok(());
+			}
+
+			if meta.path.is_ident("non_empty") {
+				res.push(Validation::NonEmpty);
+				return Ok(());
+			}
+
+			if meta.path.is_ident("regex") {
+				let pattern: LitStr = meta.value()?.parse()?;
+				if let Err(err) = regex::Regex::new(&pattern.value()) {
+					return Err(syn::Error::new(
+						pattern.span(),
+						format!("invalid regex pattern: {err}"),
+					));
+				}
+				res.push(Validation::Regex(pattern));
+				return Ok(());
+			}
+
+			if meta.path.is_ident("with") {
+				res.push(Validation::With(meta.value()?.parse()?));
+				return Ok(());
+			}
+
+			Err(meta.error("unknown validation, expected one of range, non_empty, regex or with"))
+		})?;
+	}
+
+	Ok(res)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -197,7 +359,7 @@ mod tests {
 										::config::ArgumentParseError::NotParseable {
 											name: "BAR",
 											ty: ::std::any::type_name::<u32>(),
-											source: Box::new(err) as Box<dyn ::std::error::Error>,
+											source: ::std::boxed::Box::new(err) as ::std::boxed::Box<dyn ::std::error::Error>,
 										}
 									})
 								})?,
@@ -214,4 +376,119 @@ mod tests {
 		// Assert
 		assert_eq!(expected, actual);
 	}
+
+	#[test]
+	fn default_is_used_when_missing() {
+		// Arrange
+		let input = quote! {
+			#[derive(Config)]
+			struct Foo {
+				#[env = "BAR"]
+				#[default = "8080"]
+				bar: u32
+			}
+		};
+		let input = syn::parse2::<DeriveInput>(input).expect("input should be valid DeriveInput");
+
+		let expected = quote! {
+			::std::env::var_os("BAR")
+				.or_else(|| ::std::option::Option::Some(::std::ffi::OsString::from("8080")))
+				.ok_or(::config::ArgumentParseError::Missing { name: "BAR" })
+		}
+		.to_string();
+
+		// Act
+		let actual = inner_derive_config(input).to_string();
+
+		// Assert
+		assert!(actual.contains(&expected));
+	}
+
+	#[test]
+	fn env_file_fields_are_listed_as_files() {
+		// Arrange
+		let input = quote! {
+			#[derive(Config)]
+			struct Foo {
+				#[env_file = "BAR_FILE"]
+				bar: String,
+				#[env = "BAZ"]
+				baz: u32,
+			}
+		};
+		let input = syn::parse2::<DeriveInput>(input).expect("input should be valid DeriveInput");
+
+		let expected = quote! {
+			fn files() -> ::std::vec::Vec<::std::path::PathBuf> {
+				["BAR_FILE"]
+					.into_iter()
+					.filter_map(::std::env::var_os)
+					.map(::std::path::PathBuf::from)
+					.collect()
+			}
+		}
+		.to_string();
+
+		// Act
+		let actual = inner_derive_config(input).to_string();
+
+		// Assert
+		assert!(actual.contains(&expected));
+	}
+
+	#[test]
+	fn validations_name_violated_rule() {
+		// Arrange
+		let input = quote! {
+			#[derive(Config)]
+			struct Foo {
+				#[env = "PORT"]
+				#[validate(range(1..=65535))]
+				port: u16,
+			}
+		};
+		let input = syn::parse2::<DeriveInput>(input).expect("input should be valid DeriveInput");
+
+		let expected = quote! {
+			.and_then(|value| {
+				let validity = ::config::validate::range(&value, 1..=65535);
+				validity.map(|()| value).map_err(|err| {
+					::config::ArgumentParseError::Invalid {
+						name: "PORT",
+						field: "port",
+						rule: "range(1..=65535)",
+						source: ::std::boxed::Box::new(err)
+							as ::std::boxed::Box<dyn ::std::error::Error>,
+					}
+				})
+			})?
+		}
+		.to_string();
+
+		// Act
+		let actual = inner_derive_config(input).to_string();
+
+		// Assert
+		assert!(actual.contains(&expected));
+	}
+
+	#[test]
+	fn invalid_regex_is_rejected() {
+		// Arrange
+		let input = quote! {
+			#[derive(Config)]
+			struct Foo {
+				#[env = "URL"]
+				#[validate(regex = "(")]
+				url: String,
+			}
+		};
+		let input = syn::parse2::<DeriveInput>(input).expect("input should be valid DeriveInput");
+
+		// Act
+		let actual = inner_derive_config(input).to_string();
+
+		// Assert
+		assert!(actual.starts_with(":: core :: compile_error !"));
+	}
 }
@@ -1,48 +1,60 @@
 use std::error::Error;
 
-pub trait Validator {
-	type Error: Error;
+extern crate self as constrained_str;
 
-	fn validate(v: &str) -> Result<&str, Self::Error>;
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(feature = "sqlx")]
+mod sqlx_impl;
+mod transparent_str;
+mod validation_error;
+pub mod validators;
+
+pub use constrained_str_macro::ConstrainedStr;
+pub use validation_error::*;
+
+#[doc(hidden)]
+pub mod __private {
+	#[cfg(feature = "serde")]
+	pub use serde;
+	#[cfg(feature = "sqlx")]
+	pub use sqlx;
+
+	pub use crate::transparent_str::*;
 }
 
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
 #[macro_export]
-macro_rules! constrained_str {
-	($vis:vis $name:ident, $validator:ident) => {
-		#[derive(
-			::std::fmt::Debug,
-			::std::hash::Hash,
-			::std::cmp::PartialEq,
-			::std::cmp::Eq,
-			::std::clone::Clone,
-		)]
-		$vis struct $name(::std::sync::Arc<str>);
-
-		impl $name {
-			pub fn from_str(
-				s: impl AsRef<str>,
-			) -> Result<$name, <$validator as $crate::Validator>::Error> {
-				let s = s.as_ref();
-				let checked_s = $validator::validate(s)?;
-				let checked_shared_s = ::std::sync::Arc::from(checked_s);
-				Ok($name(checked_shared_s))
-			}
-		}
+macro_rules! __impl_serde {
+	($name:ident) => {};
+}
 
-		impl ::std::str::FromStr for $name {
-			type Err = <$validator as $crate::Validator>::Error;
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_serde_unsized {
+	($name:ident) => {};
+}
 
-			fn from_str(s: &str) -> Result<Self, Self::Err> {
-				$name::from_str(s)
-			}
-		}
+#[cfg(not(feature = "sqlx"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_sqlx {
+	($name:ident) => {};
+}
 
-		impl AsRef<str> for $name {
-			fn as_ref(&self) -> &str {
-				self.0.as_ref()
-			}
-		}
-	};
+#[cfg(not(feature = "sqlx"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_sqlx_unsized {
+	($name:ident) => {};
+}
+
+pub trait Validator {
+	type Error: Error;
+
+	fn validate(v: &str) -> Result<&str, Self::Error>;
 }
 
 #[cfg(test)]
@@ -51,6 +63,7 @@ mod tests {
 	use error::InfallibleResultExt;
 	use std::convert::Infallible;
 	use std::hash::{DefaultHasher, Hash, Hasher};
+	use std::sync::Arc;
 
 	struct TestIdValidator;
 
@@ -62,7 +75,9 @@ mod tests {
 		}
 	}
 
-	constrained_str!(TestId, TestIdValidator);
+	#[derive(Debug, Clone, PartialEq, Eq, Hash, ConstrainedStr)]
+	#[constrained_str(validator = TestIdValidator)]
+	struct TestId(Arc<str>);
 
 	#[test]
 	fn create() {
@@ -83,4 +98,256 @@ mod tests {
 		// Assert
 		assert_eq!(id_hash, typed_id_hash);
 	}
+
+	#[test]
+	fn display() {
+		// Arrange
+		let typed_id = TestId::from_str("foo").unwrap_infallible();
+
+		// Act
+		let displayed = typed_id.to_string();
+
+		// Assert
+		assert_eq!(displayed, "foo");
+	}
+
+	#[derive(Debug, PartialEq, ConstrainedStr)]
+	#[constrained_str(
+		validator = (validators::Trimmed, validators::MinLength<1>, validators::MaxLength<8>)
+	)]
+	struct TestName(Arc<str>);
+
+	#[test]
+	fn combined_validators() {
+		// Arrange
+		let name = "  foo  ";
+		let blank = "   ";
+
+		// Act
+		let typed_name = TestName::from_str(name);
+		let typed_blank = TestName::from_str(blank);
+
+		// Assert
+		assert_eq!(typed_name.unwrap().as_ref(), "foo");
+		assert_eq!(
+			typed_blank,
+			Err(ValidationError::TooShort { min: 1, actual: 0 })
+		);
+	}
+
+	const fn is_lowercase(s: &str) -> bool {
+		let bytes = s.as_bytes();
+		let mut i = 0;
+		while i < bytes.len() {
+			if !bytes[i].is_ascii_lowercase() {
+				return false;
+			}
+			i += 1;
+		}
+		true
+	}
+
+	#[derive(Debug, PartialEq, Eq, Hash, ConstrainedStr)]
+	#[constrained_str(
+		validator = validators::Charset<validators::AsciiLowercase>,
+		literal = is_lowercase
+	)]
+	#[repr(transparent)]
+	struct Slug(str);
+
+	const FOO: &Slug = Slug::from_literal("foo");
+
+	#[test]
+	fn unsized_conversions() {
+		// Arrange
+		let slug = Slug::new("foo").unwrap();
+
+		// Act
+		let boxed = Box::<Slug>::from(slug);
+		let arced = Arc::<Slug>::from(slug);
+		let str_arced = arced.clone().into_str_arc();
+
+		// Assert
+		assert_eq!(&*boxed, FOO);
+		assert_eq!(&*arced, FOO);
+		assert_eq!(&*str_arced, "foo");
+		assert_eq!(Slug::from_str_arc(str_arced).unwrap(), arced);
+	}
+
+	#[test]
+	fn unsized_validates() {
+		// Arrange
+		let value = "Foo";
+
+		// Act
+		let res = Slug::new(value);
+
+		// Assert
+		assert!(matches!(
+			res,
+			Err(ValidationError::InvalidCharacter {
+				character: 'F',
+				position: 0,
+				..
+			})
+		));
+	}
+
+	#[test]
+	fn literal_agrees_with_validator() {
+		// Arrange
+		let values = ["foo", "Foo", "", "foo bar", "straße"];
+
+		// Act
+		let validated = values.map(|value| Slug::new(value).is_ok());
+
+		// Assert
+		assert_eq!(validated, values.map(is_lowercase));
+		assert!(values.into_iter().all(Slug::literal_agrees));
+		assert!(Slug::literal_agrees(FOO.as_str()));
+	}
+
+	const fn accepts_everything(_: &str) -> bool {
+		true
+	}
+
+	#[derive(Debug, PartialEq, Eq, Hash, ConstrainedStr)]
+	#[constrained_str(
+		validator = validators::Charset<validators::AsciiLowercase>,
+		literal = accepts_everything
+	)]
+	#[repr(transparent)]
+	struct DriftingSlug(str);
+
+	#[test]
+	fn literal_disagreeing_with_validator_is_detected() {
+		// Arrange
+		let value = "Foo";
+
+		// Act
+		let agrees = DriftingSlug::literal_agrees(value);
+		let res = DriftingSlug::new(value);
+
+		// Assert
+		assert!(!agrees);
+		assert!(res.is_err());
+	}
+
+	#[cfg(feature = "serde")]
+	mod serde {
+		use super::*;
+		use std::fmt::{Display, Formatter};
+
+		#[derive(Debug)]
+		struct EmptyError;
+
+		impl Display for EmptyError {
+			fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+				write!(f, "value is empty")
+			}
+		}
+
+		impl Error for EmptyError {}
+
+		struct NonEmptyValidator;
+
+		impl Validator for NonEmptyValidator {
+			type Error = EmptyError;
+
+			fn validate(v: &str) -> Result<&str, Self::Error> {
+				if v.is_empty() {
+					return Err(EmptyError);
+				}
+
+				Ok(v)
+			}
+		}
+
+		#[derive(Debug, PartialEq, ConstrainedStr)]
+		#[constrained_str(validator = NonEmptyValidator)]
+		struct NonEmpty(Arc<str>);
+
+		#[test]
+		fn round_trip() {
+			// Arrange
+			let value = NonEmpty::from_str("foo").unwrap();
+
+			// Act
+			let json = serde_json::to_string(&value).unwrap();
+			let deserialized = serde_json::from_str::<NonEmpty>(&json).unwrap();
+
+			// Assert
+			assert_eq!(json, "\"foo\"");
+			assert_eq!(deserialized, value);
+		}
+
+		#[test]
+		fn deserialize_validates() {
+			// Arrange
+			let json = "\"\"";
+
+			// Act
+			let res = serde_json::from_str::<NonEmpty>(json);
+
+			// Assert
+			assert_eq!(res.unwrap_err().to_string(), "value is empty");
+		}
+
+		#[test]
+		fn unsized_round_trip() {
+			// Arrange
+			let value = Box::<Slug>::from(FOO);
+
+			// Act
+			let json = serde_json::to_string(&value).unwrap();
+			let deserialized = serde_json::from_str::<Box<Slug>>(&json).unwrap();
+
+			// Assert
+			assert_eq!(json, "\"foo\"");
+			assert_eq!(deserialized, value);
+		}
+	}
+
+	#[cfg(feature = "sqlx")]
+	mod sqlx {
+		use super::*;
+		use ::sqlx::SqlitePool;
+
+		#[tokio::test]
+		async fn round_trip() {
+			// Arrange
+			let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+			let value = TestId::from_str("foo").unwrap_infallible();
+
+			// Act
+			let decoded: TestId = ::sqlx::query_scalar("SELECT ?")
+				.bind(value.clone())
+				.fetch_one(&pool)
+				.await
+				.unwrap();
+
+			// Assert
+			assert_eq!(decoded, value);
+		}
+
+		#[tokio::test]
+		async fn unsized_decode_validates() {
+			// Arrange
+			let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+
+			// Act
+			let valid: Box<Slug> = ::sqlx::query_scalar("SELECT ?")
+				.bind(FOO)
+				.fetch_one(&pool)
+				.await
+				.unwrap();
+			let invalid = ::sqlx::query_scalar::<_, Box<Slug>>("SELECT 'Foo'")
+				.fetch_one(&pool)
+				.await;
+
+			// Assert
+			assert_eq!(&*valid, FOO);
+			assert!(matches!(invalid, Err(::sqlx::Error::ColumnDecode { .. })));
+		}
+	}
 }
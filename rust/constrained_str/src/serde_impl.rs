@@ -0,0 +1,62 @@
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_serde {
+	($name:ident) => {
+		#[automatically_derived]
+		impl $crate::__private::serde::Serialize for $name {
+			fn serialize<S: $crate::__private::serde::Serializer>(
+				&self,
+				serializer: S,
+			) -> Result<S::Ok, S::Error> {
+				serializer.serialize_str(self.as_ref())
+			}
+		}
+
+		#[automatically_derived]
+		impl<'de> $crate::__private::serde::Deserialize<'de> for $name {
+			fn deserialize<D: $crate::__private::serde::Deserializer<'de>>(
+				deserializer: D,
+			) -> Result<Self, D::Error> {
+				let s =
+					<::std::string::String as $crate::__private::serde::Deserialize>::deserialize(
+						deserializer,
+					)?;
+
+				$name::from_str(s)
+					.map_err(<D::Error as $crate::__private::serde::de::Error>::custom)
+			}
+		}
+	};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_serde_unsized {
+	($name:ident) => {
+		#[automatically_derived]
+		impl $crate::__private::serde::Serialize for $name {
+			fn serialize<S: $crate::__private::serde::Serializer>(
+				&self,
+				serializer: S,
+			) -> Result<S::Ok, S::Error> {
+				serializer.serialize_str(self.as_ref())
+			}
+		}
+
+		#[automatically_derived]
+		impl<'de> $crate::__private::serde::Deserialize<'de> for ::std::boxed::Box<$name> {
+			fn deserialize<D: $crate::__private::serde::Deserializer<'de>>(
+				deserializer: D,
+			) -> Result<Self, D::Error> {
+				let s =
+					<::std::string::String as $crate::__private::serde::Deserialize>::deserialize(
+						deserializer,
+					)?;
+
+				$name::new(&s)
+					.map(::std::boxed::Box::from)
+					.map_err(<D::Error as $crate::__private::serde::de::Error>::custom)
+			}
+		}
+	};
+}
@@ -0,0 +1,142 @@
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_sqlx {
+	($name:ident) => {
+		#[automatically_derived]
+		impl<DB: $crate::__private::sqlx::Database> $crate::__private::sqlx::Type<DB> for $name
+		where
+			str: $crate::__private::sqlx::Type<DB>,
+		{
+			fn type_info() -> DB::TypeInfo {
+				<str as $crate::__private::sqlx::Type<DB>>::type_info()
+			}
+
+			fn compatible(ty: &DB::TypeInfo) -> bool {
+				<str as $crate::__private::sqlx::Type<DB>>::compatible(ty)
+			}
+		}
+
+		#[automatically_derived]
+		impl<'q, DB: $crate::__private::sqlx::Database> $crate::__private::sqlx::Encode<'q, DB>
+			for $name
+		where
+			::std::string::String: $crate::__private::sqlx::Encode<'q, DB>,
+		{
+			fn encode_by_ref(
+				&self,
+				buf: &mut <DB as $crate::__private::sqlx::Database>::ArgumentBuffer<'q>,
+			) -> Result<
+				$crate::__private::sqlx::encode::IsNull,
+				$crate::__private::sqlx::error::BoxDynError,
+			> {
+				<::std::string::String as $crate::__private::sqlx::Encode<'q, DB>>::encode(
+					::std::string::String::from(::std::convert::AsRef::<str>::as_ref(self)),
+					buf,
+				)
+			}
+		}
+
+		#[automatically_derived]
+		impl<'r, DB: $crate::__private::sqlx::Database> $crate::__private::sqlx::Decode<'r, DB>
+			for $name
+		where
+			&'r str: $crate::__private::sqlx::Decode<'r, DB>,
+		{
+			fn decode(
+				value: <DB as $crate::__private::sqlx::Database>::ValueRef<'r>,
+			) -> Result<Self, $crate::__private::sqlx::error::BoxDynError> {
+				let s = <&'r str as $crate::__private::sqlx::Decode<'r, DB>>::decode(value)?;
+				Ok($name::from_str(s)?)
+			}
+		}
+	};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_sqlx_unsized {
+	($name:ident) => {
+		#[automatically_derived]
+		impl<DB: $crate::__private::sqlx::Database> $crate::__private::sqlx::Type<DB> for $name
+		where
+			str: $crate::__private::sqlx::Type<DB>,
+		{
+			fn type_info() -> DB::TypeInfo {
+				<str as $crate::__private::sqlx::Type<DB>>::type_info()
+			}
+
+			fn compatible(ty: &DB::TypeInfo) -> bool {
+				<str as $crate::__private::sqlx::Type<DB>>::compatible(ty)
+			}
+		}
+
+		#[automatically_derived]
+		impl<DB: $crate::__private::sqlx::Database> $crate::__private::sqlx::Type<DB>
+			for ::std::boxed::Box<$name>
+		where
+			str: $crate::__private::sqlx::Type<DB>,
+		{
+			fn type_info() -> DB::TypeInfo {
+				<str as $crate::__private::sqlx::Type<DB>>::type_info()
+			}
+
+			fn compatible(ty: &DB::TypeInfo) -> bool {
+				<str as $crate::__private::sqlx::Type<DB>>::compatible(ty)
+			}
+		}
+
+		#[automatically_derived]
+		impl<'q, DB: $crate::__private::sqlx::Database> $crate::__private::sqlx::Encode<'q, DB>
+			for &$name
+		where
+			::std::string::String: $crate::__private::sqlx::Encode<'q, DB>,
+		{
+			fn encode_by_ref(
+				&self,
+				buf: &mut <DB as $crate::__private::sqlx::Database>::ArgumentBuffer<'q>,
+			) -> Result<
+				$crate::__private::sqlx::encode::IsNull,
+				$crate::__private::sqlx::error::BoxDynError,
+			> {
+				<::std::string::String as $crate::__private::sqlx::Encode<'q, DB>>::encode(
+					::std::string::String::from(::std::convert::AsRef::<str>::as_ref(self)),
+					buf,
+				)
+			}
+		}
+
+		#[automatically_derived]
+		impl<'q, DB: $crate::__private::sqlx::Database> $crate::__private::sqlx::Encode<'q, DB>
+			for ::std::boxed::Box<$name>
+		where
+			::std::string::String: $crate::__private::sqlx::Encode<'q, DB>,
+		{
+			fn encode_by_ref(
+				&self,
+				buf: &mut <DB as $crate::__private::sqlx::Database>::ArgumentBuffer<'q>,
+			) -> Result<
+				$crate::__private::sqlx::encode::IsNull,
+				$crate::__private::sqlx::error::BoxDynError,
+			> {
+				<::std::string::String as $crate::__private::sqlx::Encode<'q, DB>>::encode(
+					::std::string::String::from(::std::convert::AsRef::<str>::as_ref(&**self)),
+					buf,
+				)
+			}
+		}
+
+		#[automatically_derived]
+		impl<'r, DB: $crate::__private::sqlx::Database> $crate::__private::sqlx::Decode<'r, DB>
+			for ::std::boxed::Box<$name>
+		where
+			&'r str: $crate::__private::sqlx::Decode<'r, DB>,
+		{
+			fn decode(
+				value: <DB as $crate::__private::sqlx::Database>::ValueRef<'r>,
+			) -> Result<Self, $crate::__private::sqlx::error::BoxDynError> {
+				let s = <&'r str as $crate::__private::sqlx::Decode<'r, DB>>::decode(value)?;
+				Ok(::std::boxed::Box::from($name::new(s)?))
+			}
+		}
+	};
+}
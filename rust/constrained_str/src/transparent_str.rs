@@ -0,0 +1,47 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// A `#[repr(transparent)]` wrapper of `str`.
+///
+/// # Safety
+///
+/// Implementors must have the same layout as `str`, which `#[derive(ConstrainedStr)]` ensures by
+/// requiring `#[repr(transparent)]`.
+pub unsafe trait TransparentStr {
+	fn from_str_ptr(ptr: *const str) -> *const Self;
+
+	fn into_str_ptr(ptr: *const Self) -> *const str;
+}
+
+pub fn cast_ref<T: TransparentStr + ?Sized>(s: &str) -> &T {
+	// SAFETY: T has the same layout as str.
+	unsafe { &*T::from_str_ptr(s) }
+}
+
+pub fn cast_box<T: TransparentStr + ?Sized>(s: Box<str>) -> Box<T> {
+	let ptr = T::from_str_ptr(Box::into_raw(s));
+
+	// SAFETY: T has the same layout as str and the pointer stems from a box.
+	unsafe { Box::from_raw(ptr as *mut T) }
+}
+
+pub fn cast_arc<T: TransparentStr + ?Sized>(s: Arc<str>) -> Arc<T> {
+	let ptr = T::from_str_ptr(Arc::into_raw(s));
+
+	// SAFETY: T has the same layout as str and the pointer stems from an arc.
+	unsafe { Arc::from_raw(ptr) }
+}
+
+pub fn cast_rc<T: TransparentStr + ?Sized>(s: Rc<str>) -> Rc<T> {
+	let ptr = T::from_str_ptr(Rc::into_raw(s));
+
+	// SAFETY: T has the same layout as str and the pointer stems from an rc.
+	unsafe { Rc::from_raw(ptr) }
+}
+
+pub fn uncast_arc<T: TransparentStr + ?Sized>(value: Arc<T>) -> Arc<str> {
+	let ptr = T::into_str_ptr(Arc::into_raw(value));
+
+	// SAFETY: T has the same layout as str and the pointer stems from an arc.
+	unsafe { Arc::from_raw(ptr) }
+}
@@ -0,0 +1,37 @@
+use std::convert::Infallible;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ValidationError {
+	#[error("expected at least {min} characters, got {actual}")]
+	TooShort { min: usize, actual: usize },
+	#[error("expected at most {max} characters, got {actual}")]
+	TooLong { max: usize, actual: usize },
+	#[error("expected no leading or trailing whitespace")]
+	SurroundingWhitespace,
+	#[error("unexpected character {character:?} at byte {position}, expected {expected}")]
+	InvalidCharacter {
+		character: char,
+		position: usize,
+		expected: &'static str,
+	},
+	#[error("expected prefix {prefix:?}")]
+	MissingPrefix { prefix: &'static str },
+	#[error("expected match of pattern {pattern:?}")]
+	PatternMismatch { pattern: &'static str },
+	#[error("pattern {pattern:?} is not a valid regular expression, {reason}")]
+	InvalidPattern {
+		pattern: &'static str,
+		reason: String,
+	},
+	#[error("expected hyphenated UUID")]
+	InvalidUuid,
+	#[error("expected IBAN, {reason}")]
+	InvalidIban { reason: &'static str },
+}
+
+impl From<Infallible> for ValidationError {
+	fn from(value: Infallible) -> Self {
+		match value {}
+	}
+}
@@ -0,0 +1,499 @@
+//! Built-in [`Validator`]s.
+//!
+//! Validators compose by listing several of them as a tuple in the `validator` option of
+//! [`ConstrainedStr`](crate::ConstrainedStr), in which case they are applied in order, each one
+//! receiving the `str` returned by the previous one. This allows e.g. [`Trimmed`] to strip
+//! whitespace before the length is checked.
+
+use crate::{ValidationError, Validator};
+use std::convert::Infallible;
+use std::marker::PhantomData;
+
+/// Accepts every `str`.
+pub struct Unconstrained;
+
+impl Validator for Unconstrained {
+	type Error = Infallible;
+
+	fn validate(v: &str) -> Result<&str, Self::Error> {
+		Ok(v)
+	}
+}
+
+/// Requires at least `N` characters.
+pub struct MinLength<const N: usize>;
+
+impl<const N: usize> Validator for MinLength<N> {
+	type Error = ValidationError;
+
+	fn validate(v: &str) -> Result<&str, Self::Error> {
+		let actual = v.chars().count();
+
+		if actual < N {
+			return Err(ValidationError::TooShort { min: N, actual });
+		}
+
+		Ok(v)
+	}
+}
+
+/// Requires at most `N` characters.
+pub struct MaxLength<const N: usize>;
+
+impl<const N: usize> Validator for MaxLength<N> {
+	type Error = ValidationError;
+
+	fn validate(v: &str) -> Result<&str, Self::Error> {
+		let actual = v.chars().count();
+
+		if actual > N {
+			return Err(ValidationError::TooLong { max: N, actual });
+		}
+
+		Ok(v)
+	}
+}
+
+/// Strips leading and trailing whitespace.
+pub struct Trimmed;
+
+impl Validator for Trimmed {
+	type Error = Infallible;
+
+	fn validate(v: &str) -> Result<&str, Self::Error> {
+		Ok(v.trim())
+	}
+}
+
+/// Rejects leading and trailing whitespace.
+pub struct NoSurroundingWhitespace;
+
+impl Validator for NoSurroundingWhitespace {
+	type Error = ValidationError;
+
+	fn validate(v: &str) -> Result<&str, Self::Error> {
+		if v.trim() != v {
+			return Err(ValidationError::SurroundingWhitespace);
+		}
+
+		Ok(v)
+	}
+}
+
+pub trait CharClass {
+	fn name() -> &'static str;
+
+	fn contains(c: char) -> bool;
+}
+
+macro_rules! char_class {
+	($( $name:ident => $predicate:ident ),* $(,)?) => {
+		$(
+			pub struct $name;
+
+			impl CharClass for $name {
+				fn name() -> &'static str {
+					stringify!($name)
+				}
+
+				fn contains(c: char) -> bool {
+					c.$predicate()
+				}
+			}
+		)*
+	};
+}
+
+char_class!(
+	AsciiAlphabetic => is_ascii_alphabetic,
+	AsciiAlphanumeric => is_ascii_alphanumeric,
+	AsciiDigit => is_ascii_digit,
+	AsciiHexDigit => is_ascii_hexdigit,
+	AsciiLowercase => is_ascii_lowercase,
+	AsciiUppercase => is_ascii_uppercase,
+	AsciiGraphic => is_ascii_graphic,
+);
+
+/// Character class containing the characters of either `A` or `B`.
+pub struct Or<A, B>(PhantomData<(A, B)>);
+
+impl<A: CharClass, B: CharClass> CharClass for Or<A, B> {
+	fn name() -> &'static str {
+		std::any::type_name::<Self>()
+	}
+
+	fn contains(c: char) -> bool {
+		A::contains(c) || B::contains(c)
+	}
+}
+
+/// Character class containing the characters of the string literal `L`.
+pub struct OneOf<L>(PhantomData<L>);
+
+impl<L: Literal> CharClass for OneOf<L> {
+	fn name() -> &'static str {
+		L::VALUE
+	}
+
+	fn contains(c: char) -> bool {
+		L::VALUE.contains(c)
+	}
+}
+
+/// Requires every character to be part of the character class `C`.
+pub struct Charset<C>(PhantomData<C>);
+
+impl<C: CharClass> Validator for Charset<C> {
+	type Error = ValidationError;
+
+	fn validate(v: &str) -> Result<&str, Self::Error> {
+		let invalid = v.char_indices().find(|(_, c)| !C::contains(*c));
+
+		if let Some((position, character)) = invalid {
+			return Err(ValidationError::InvalidCharacter {
+				character,
+				position,
+				expected: C::name(),
+			});
+		}
+
+		Ok(v)
+	}
+}
+
+/// A string literal lifted to the type level, declared using [`literal!`](crate::literal).
+pub trait Literal {
+	const VALUE: &'static str;
+}
+
+#[macro_export]
+macro_rules! literal {
+	($vis:vis $name:ident = $value:literal) => {
+		$vis struct $name;
+
+		impl $crate::validators::Literal for $name {
+			const VALUE: &'static str = $value;
+		}
+	};
+}
+
+/// Requires the `str` to start with the literal `L`.
+pub struct StartsWith<L>(PhantomData<L>);
+
+impl<L: Literal> Validator for StartsWith<L> {
+	type Error = ValidationError;
+
+	fn validate(v: &str) -> Result<&str, Self::Error> {
+		if !v.starts_with(L::VALUE) {
+			return Err(ValidationError::MissingPrefix { prefix: L::VALUE });
+		}
+
+		Ok(v)
+	}
+}
+
+/// Requires the `str` to match the regular expression `L`.
+///
+/// The expression is not implicitly anchored, use `^` and `$` to match the entire `str`. It is
+/// compiled once, an invalid expression rejects every `str` with
+/// [`ValidationError::InvalidPattern`].
+#[cfg(feature = "regex")]
+pub struct Regex<L>(PhantomData<L>);
+
+#[cfg(feature = "regex")]
+impl<L: Literal> Validator for Regex<L> {
+	type Error = ValidationError;
+
+	fn validate(v: &str) -> Result<&str, Self::Error> {
+		use std::collections::HashMap;
+		use std::sync::{OnceLock, RwLock};
+
+		type Compiled = Result<regex::Regex, regex::Error>;
+
+		static CACHE: OnceLock<RwLock<HashMap<&'static str, Compiled>>> = OnceLock::new();
+		let cache = CACHE.get_or_init(Default::default);
+
+		let cached = cache
+			.read()
+			.expect("lock should not be poisoned")
+			.get(L::VALUE)
+			.cloned();
+
+		let compiled = match cached {
+			Some(compiled) => compiled,
+			None => {
+				let compiled = regex::Regex::new(L::VALUE);
+				cache
+					.write()
+					.expect("lock should not be poisoned")
+					.insert(L::VALUE, compiled.clone());
+				compiled
+			}
+		};
+
+		let regex = compiled.map_err(|err| ValidationError::InvalidPattern {
+			pattern: L::VALUE,
+			reason: err.to_string(),
+		})?;
+
+		if !regex.is_match(v) {
+			return Err(ValidationError::PatternMismatch { pattern: L::VALUE });
+		}
+
+		Ok(v)
+	}
+}
+
+/// Requires a hyphenated UUID, e.g. `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+pub struct Uuid;
+
+impl Validator for Uuid {
+	type Error = ValidationError;
+
+	fn validate(v: &str) -> Result<&str, Self::Error> {
+		let groups = v.split('-').map(str::len).collect::<Vec<_>>();
+		let is_hex = v.chars().all(|c| c == '-' || c.is_ascii_hexdigit());
+
+		if groups != [8, 4, 4, 4, 12] || !is_hex {
+			return Err(ValidationError::InvalidUuid);
+		}
+
+		Ok(v)
+	}
+}
+
+/// Requires an IBAN in electronic format, i.e. without spaces, with valid check digits.
+///
+/// The country specific length and structure is not checked.
+pub struct Iban;
+
+impl Validator for Iban {
+	type Error = ValidationError;
+
+	fn validate(v: &str) -> Result<&str, Self::Error> {
+		if !v.is_ascii() {
+			return Err(ValidationError::InvalidIban {
+				reason: "must only contain digits and upper case letters",
+			});
+		}
+
+		if !(15..=34).contains(&v.len()) {
+			return Err(ValidationError::InvalidIban {
+				reason: "length must be between 15 and 34 characters",
+			});
+		}
+
+		let (country, rest) = v.split_at(2);
+		let (check_digits, bban) = rest.split_at(2);
+
+		if !country.chars().all(|c| c.is_ascii_uppercase()) {
+			return Err(ValidationError::InvalidIban {
+				reason: "must start with a country code",
+			});
+		}
+
+		if !check_digits.chars().all(|c| c.is_ascii_digit()) {
+			return Err(ValidationError::InvalidIban {
+				reason: "country code must be followed by two check digits",
+			});
+		}
+
+		if !bban
+			.chars()
+			.all(|c| c.is_ascii_digit() || c.is_ascii_uppercase())
+		{
+			return Err(ValidationError::InvalidIban {
+				reason: "must only contain digits and upper case letters",
+			});
+		}
+
+		let remainder = bban
+			.chars()
+			.chain(country.chars())
+			.chain(check_digits.chars())
+			.map(|c| c.to_digit(36).expect("checked to be alphanumeric"))
+			.fold(0, |remainder, digit| {
+				let shift = if digit < 10 { 10 } else { 100 };
+				(remainder * shift + digit) % 97
+			});
+
+		if remainder != 1 {
+			return Err(ValidationError::InvalidIban {
+				reason: "check digits do not match",
+			});
+		}
+
+		Ok(v)
+	}
+}
+
+macro_rules! impl_validator_for_tuple {
+	($( $validator:ident ),+) => {
+		impl<$( $validator: Validator ),+> Validator for ($( $validator, )+)
+		where
+			$( ValidationError: From<$validator::Error>, )+
+		{
+			type Error = ValidationError;
+
+			fn validate(v: &str) -> Result<&str, Self::Error> {
+				$( let v = $validator::validate(v)?; )+
+				Ok(v)
+			}
+		}
+	};
+}
+
+impl_validator_for_tuple!(A);
+impl_validator_for_tuple!(A, B);
+impl_validator_for_tuple!(A, B, C);
+impl_validator_for_tuple!(A, B, C, D);
+impl_validator_for_tuple!(A, B, C, D, E);
+impl_validator_for_tuple!(A, B, C, D, E, F);
+impl_validator_for_tuple!(A, B, C, D, E, F, G);
+impl_validator_for_tuple!(A, B, C, D, E, F, G, H);
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn length() {
+		// Arrange
+		type Length = (MinLength<2>, MaxLength<3>);
+
+		// Act
+		let too_short = Length::validate("a");
+		let ok = Length::validate("ab");
+		let too_long = Length::validate("abcd");
+
+		// Assert
+		assert_eq!(
+			too_short,
+			Err(ValidationError::TooShort { min: 2, actual: 1 })
+		);
+		assert_eq!(ok, Ok("ab"));
+		assert_eq!(
+			too_long,
+			Err(ValidationError::TooLong { max: 3, actual: 4 })
+		);
+	}
+
+	#[test]
+	fn trimmed_before_length_check() {
+		// Arrange
+		type TrimmedLength = (Trimmed, MaxLength<3>);
+
+		// Act
+		let res = TrimmedLength::validate("  abc  ");
+
+		// Assert
+		assert_eq!(res, Ok("abc"));
+	}
+
+	#[test]
+	fn charset() {
+		// Arrange
+		crate::literal!(Underscore = "_");
+		type Identifier = Charset<Or<AsciiAlphanumeric, OneOf<Underscore>>>;
+
+		// Act
+		let ok = Identifier::validate("SANDBOX_SFIN0000");
+		let err = Identifier::validate("SANDBOX-SFIN0000");
+
+		// Assert
+		assert_eq!(ok, Ok("SANDBOX_SFIN0000"));
+		assert!(matches!(
+			err,
+			Err(ValidationError::InvalidCharacter {
+				character: '-',
+				position: 7,
+				..
+			})
+		));
+	}
+
+	#[test]
+	fn starts_with() {
+		// Arrange
+		crate::literal!(Prefix = "sk_");
+
+		// Act
+		let ok = StartsWith::<Prefix>::validate("sk_123");
+		let err = StartsWith::<Prefix>::validate("pk_123");
+
+		// Assert
+		assert_eq!(ok, Ok("sk_123"));
+		assert_eq!(err, Err(ValidationError::MissingPrefix { prefix: "sk_" }));
+	}
+
+	#[test]
+	fn uuid() {
+		// Arrange
+		let valid = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+		let invalid = "67e55044-10b1-426f-9247-bb680e5fe0cg";
+
+		// Act
+		let ok = Uuid::validate(valid);
+		let err = Uuid::validate(invalid);
+
+		// Assert
+		assert_eq!(ok, Ok(valid));
+		assert_eq!(err, Err(ValidationError::InvalidUuid));
+	}
+
+	#[test]
+	fn iban() {
+		// Arrange
+		let valid = "GB82WEST12345698765432";
+		let invalid = "GB83WEST12345698765432";
+
+		// Act
+		let ok = Iban::validate(valid);
+		let err = Iban::validate(invalid);
+
+		// Assert
+		assert_eq!(ok, Ok(valid));
+		assert_eq!(
+			err,
+			Err(ValidationError::InvalidIban {
+				reason: "check digits do not match",
+			})
+		);
+	}
+
+	#[cfg(feature = "regex")]
+	#[test]
+	fn regex() {
+		// Arrange
+		crate::literal!(Digits = "^[0-9]+$");
+
+		// Act
+		let ok = Regex::<Digits>::validate("123");
+		let err = Regex::<Digits>::validate("12a");
+
+		// Assert
+		assert_eq!(ok, Ok("123"));
+		assert_eq!(
+			err,
+			Err(ValidationError::PatternMismatch {
+				pattern: "^[0-9]+$",
+			})
+		);
+	}
+
+	#[cfg(feature = "regex")]
+	#[test]
+	fn invalid_regex() {
+		// Arrange
+		crate::literal!(Unclosed = "(");
+
+		// Act
+		let err = Regex::<Unclosed>::validate("(");
+
+		// Assert
+		assert!(matches!(
+			err,
+			Err(ValidationError::InvalidPattern { pattern: "(", .. })
+		));
+	}
+}
@@ -0,0 +1,401 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, Path, Type};
+
+#[proc_macro_derive(ConstrainedStr, attributes(constrained_str))]
+pub fn derive_constrained_str(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	inner_derive_constrained_str(input).into()
+}
+
+fn inner_derive_constrained_str(input: DeriveInput) -> TokenStream {
+	if !input.generics.params.is_empty() {
+		return quote_spanned!(input.generics.span()=> compile_error!("generic constrained strings are not supported"););
+	}
+
+	let Data::Struct(data) = &input.data else {
+		return quote_spanned!(input.span()=> compile_error!("expected struct"););
+	};
+
+	let Fields::Unnamed(fields) = &data.fields else {
+		return quote_spanned!(data.fields.span()=> compile_error!("expected tuple struct"););
+	};
+
+	let mut fields = fields.unnamed.iter();
+	let (Some(field), None) = (fields.next(), fields.next()) else {
+		return quote_spanned!(data.fields.span()=> compile_error!("expected exactly one field"););
+	};
+
+	let options = match Options::from_attributes(&input.attrs) {
+		Err(err) => return err.to_compile_error(),
+		Ok(v) => v,
+	};
+
+	match Representation::of(&field.ty) {
+		Some(Representation::Owned) => {
+			if let Some(literal) = &options.literal {
+				return quote_spanned!(literal.span()=> compile_error!("literals are only supported for unsized constrained strings"););
+			}
+
+			derive_owned(&input.ident, &options)
+		}
+
+		Some(Representation::Unsized) => {
+			if !is_repr_transparent(&input.attrs) {
+				return quote_spanned!(input.ident.span()=> compile_error!("unsized constrained strings must be #[repr(transparent)]"););
+			}
+
+			derive_unsized(&input.ident, &options)
+		}
+
+		None => {
+			quote_spanned!(field.ty.span()=> compile_error!("expected field of type Arc<str> or str");)
+		}
+	}
+}
+
+struct Options {
+	validator: Type,
+	literal: Option<Path>,
+}
+
+impl Options {
+	fn from_attributes(attributes: &[Attribute]) -> syn::Result<Options> {
+		let mut validator = None;
+		let mut literal = None;
+		for attribute in attributes {
+			if !attribute.path().is_ident("constrained_str") {
+				continue;
+			}
+
+			attribute.parse_nested_meta(|meta| {
+				if meta.path.is_ident("validator") {
+					if validator.replace(meta.value()?.parse()?).is_some() {
+						return Err(meta.error("duplicate validator"));
+					}
+					return Ok(());
+				}
+
+				if meta.path.is_ident("literal") {
+					if literal.replace(meta.value()?.parse()?).is_some() {
+						return Err(meta.error("duplicate literal"));
+					}
+					return Ok(());
+				}
+
+				Err(meta.error("unknown option, expected one of validator or literal"))
+			})?;
+		}
+
+		let validator = validator
+			.unwrap_or_else(|| syn::parse_quote!(::constrained_str::validators::Unconstrained));
+
+		Ok(Options { validator, literal })
+	}
+}
+
+enum Representation {
+	Owned,
+	Unsized,
+}
+
+impl Representation {
+	fn of(ty: &Type) -> Option<Representation> {
+		let Type::Path(path) = ty else {
+			return None;
+		};
+
+		if path.path.is_ident("str") {
+			return Some(Representation::Unsized);
+		}
+
+		let last = path.path.segments.last()?;
+		let syn::PathArguments::AngleBracketed(arguments) = &last.arguments else {
+			return None;
+		};
+
+		let is_arc_of_str = last.ident == "Arc"
+			&& arguments.args.len() == 1
+			&& matches!(
+				arguments.args.first(),
+				Some(syn::GenericArgument::Type(Type::Path(inner))) if inner.path.is_ident("str")
+			);
+
+		is_arc_of_str.then_some(Representation::Owned)
+	}
+}
+
+fn is_repr_transparent(attributes: &[Attribute]) -> bool {
+	attributes
+		.iter()
+		.filter(|attribute| attribute.path().is_ident("repr"))
+		.any(|attribute| {
+			let mut transparent = false;
+			let _ = attribute.parse_nested_meta(|meta| {
+				transparent |= meta.path.is_ident("transparent");
+				Ok(())
+			});
+			transparent
+		})
+}
+
+fn derive_owned(name: &syn::Ident, options: &Options) -> TokenStream {
+	let validator = &options.validator;
+
+	quote! {
+		impl #name {
+			pub fn from_str(
+				s: impl ::std::convert::AsRef<str>,
+			) -> ::std::result::Result<#name, <#validator as ::constrained_str::Validator>::Error> {
+				let s = s.as_ref();
+				let checked_s = <#validator as ::constrained_str::Validator>::validate(s)?;
+				let checked_shared_s = ::std::sync::Arc::from(checked_s);
+				::std::result::Result::Ok(#name(checked_shared_s))
+			}
+		}
+
+		#[automatically_derived]
+		impl ::std::str::FromStr for #name {
+			type Err = <#validator as ::constrained_str::Validator>::Error;
+
+			fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+				#name::from_str(s)
+			}
+		}
+
+		#[automatically_derived]
+		impl ::std::convert::AsRef<str> for #name {
+			fn as_ref(&self) -> &str {
+				self.0.as_ref()
+			}
+		}
+
+		#[automatically_derived]
+		impl ::std::borrow::Borrow<str> for #name {
+			fn borrow(&self) -> &str {
+				self.0.as_ref()
+			}
+		}
+
+		#[automatically_derived]
+		impl ::std::fmt::Display for #name {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				::std::fmt::Display::fmt(&self.0, f)
+			}
+		}
+
+		::constrained_str::__impl_serde!(#name);
+		::constrained_str::__impl_sqlx!(#name);
+	}
+}
+
+fn derive_unsized(name: &syn::Ident, options: &Options) -> TokenStream {
+	let validator = &options.validator;
+
+	let from_literal = options.literal.as_ref().map(|literal| {
+		let message = format!("literal is not a valid {name}");
+
+		quote! {
+			impl #name {
+				pub const fn from_literal(s: &'static str) -> &'static #name {
+					if !#literal(s) {
+						::std::panic!(#message);
+					}
+
+					// SAFETY: the derived type is a #[repr(transparent)] wrapper of str, as checked when
+					// deriving ConstrainedStr.
+					unsafe { &*(s as *const str as *const #name) }
+				}
+
+				/// Whether the validator accepts `s` as is, if [`Self::from_literal`] does. The validator
+				/// can't be evaluated in a const fn, hence this is for tests to check the literals used.
+				pub fn literal_agrees(s: &str) -> bool {
+					let checked_s = <#validator as ::constrained_str::Validator>::validate(s);
+					!#literal(s) || checked_s.is_ok_and(|checked_s| ::std::ptr::eq(checked_s, s))
+				}
+			}
+		}
+	});
+
+	quote! {
+		// SAFETY: the derived type is a #[repr(transparent)] wrapper of str, as checked when
+		// deriving ConstrainedStr.
+		#[automatically_derived]
+		unsafe impl ::constrained_str::__private::TransparentStr for #name {
+			fn from_str_ptr(ptr: *const str) -> *const Self {
+				ptr as *const Self
+			}
+
+			fn into_str_ptr(ptr: *const Self) -> *const str {
+				ptr as *const str
+			}
+		}
+
+		impl #name {
+			pub fn new(
+				s: &str,
+			) -> ::std::result::Result<&#name, <#validator as ::constrained_str::Validator>::Error> {
+				let checked_s = <#validator as ::constrained_str::Validator>::validate(s)?;
+				::std::result::Result::Ok(::constrained_str::__private::cast_ref(checked_s))
+			}
+
+			pub fn from_str_arc(
+				value: ::std::sync::Arc<str>,
+			) -> ::std::result::Result<
+				::std::sync::Arc<#name>,
+				<#validator as ::constrained_str::Validator>::Error,
+			> {
+				let checked_s = <#validator as ::constrained_str::Validator>::validate(&value)?;
+				if ::std::ptr::eq(checked_s, &*value) {
+					return ::std::result::Result::Ok(::constrained_str::__private::cast_arc(value));
+				}
+
+				let checked_s = ::std::sync::Arc::from(checked_s);
+				::std::result::Result::Ok(::constrained_str::__private::cast_arc(checked_s))
+			}
+
+			pub fn into_str_arc(self: ::std::sync::Arc<#name>) -> ::std::sync::Arc<str> {
+				::constrained_str::__private::uncast_arc(self)
+			}
+
+			pub fn as_str(&self) -> &str {
+				&self.0
+			}
+		}
+
+		#from_literal
+
+		#[automatically_derived]
+		// Unconstrained strings can't fail to convert, but still get TryFrom like all others.
+		#[allow(clippy::infallible_try_from)]
+		impl<'a> ::std::convert::TryFrom<&'a str> for &'a #name {
+			type Error = <#validator as ::constrained_str::Validator>::Error;
+
+			fn try_from(s: &'a str) -> ::std::result::Result<Self, Self::Error> {
+				#name::new(s)
+			}
+		}
+
+		#[automatically_derived]
+		impl ::std::convert::AsRef<str> for #name {
+			fn as_ref(&self) -> &str {
+				&self.0
+			}
+		}
+
+		#[automatically_derived]
+		impl ::std::borrow::Borrow<str> for #name {
+			fn borrow(&self) -> &str {
+				&self.0
+			}
+		}
+
+		#[automatically_derived]
+		impl ::std::fmt::Display for #name {
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+				::std::fmt::Display::fmt(&self.0, f)
+			}
+		}
+
+		#[automatically_derived]
+		impl ::std::borrow::ToOwned for #name {
+			type Owned = ::std::boxed::Box<#name>;
+
+			fn to_owned(&self) -> Self::Owned {
+				::std::boxed::Box::from(self)
+			}
+		}
+
+		#[automatically_derived]
+		impl ::std::clone::Clone for ::std::boxed::Box<#name> {
+			fn clone(&self) -> Self {
+				::std::boxed::Box::from(&**self)
+			}
+		}
+
+		#[automatically_derived]
+		impl ::std::convert::From<&#name> for ::std::boxed::Box<#name> {
+			fn from(value: &#name) -> Self {
+				::constrained_str::__private::cast_box(::std::boxed::Box::<str>::from(&value.0))
+			}
+		}
+
+		#[automatically_derived]
+		impl ::std::convert::From<&#name> for ::std::sync::Arc<#name> {
+			fn from(value: &#name) -> Self {
+				::constrained_str::__private::cast_arc(::std::sync::Arc::<str>::from(&value.0))
+			}
+		}
+
+		#[automatically_derived]
+		impl ::std::convert::From<&#name> for ::std::rc::Rc<#name> {
+			fn from(value: &#name) -> Self {
+				::constrained_str::__private::cast_rc(::std::rc::Rc::<str>::from(&value.0))
+			}
+		}
+
+		::constrained_str::__impl_serde_unsized!(#name);
+		::constrained_str::__impl_sqlx_unsized!(#name);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn validator_defaults_to_unconstrained() {
+		// Arrange
+		let input = quote! {
+			#[derive(ConstrainedStr)]
+			struct Foo(Arc<str>);
+		};
+		let input = syn::parse2::<DeriveInput>(input).expect("input should be valid DeriveInput");
+
+		let expected = quote! {
+			type Err = <::constrained_str::validators::Unconstrained as ::constrained_str::Validator>::Error;
+		}
+		.to_string();
+
+		// Act
+		let actual = inner_derive_constrained_str(input).to_string();
+
+		// Assert
+		assert!(actual.contains(&expected));
+	}
+
+	#[test]
+	fn unsized_requires_repr_transparent() {
+		// Arrange
+		let input = quote! {
+			#[derive(ConstrainedStr)]
+			struct Foo(str);
+		};
+		let input = syn::parse2::<DeriveInput>(input).expect("input should be valid DeriveInput");
+
+		// Act
+		let actual = inner_derive_constrained_str(input).to_string();
+
+		// Assert
+		assert!(actual.contains("compile_error"));
+		assert!(actual.contains("repr(transparent)"));
+	}
+
+	#[test]
+	fn literal_requires_unsized() {
+		// Arrange
+		let input = quote! {
+			#[derive(ConstrainedStr)]
+			#[constrained_str(literal = is_valid)]
+			struct Foo(Arc<str>);
+		};
+		let input = syn::parse2::<DeriveInput>(input).expect("input should be valid DeriveInput");
+
+		// Act
+		let actual = inner_derive_constrained_str(input).to_string();
+
+		// Assert
+		assert!(actual.contains("compile_error"));
+	}
+}
@@ -0,0 +1,173 @@
+use crate::report::offer_backtrace;
+use crate::{ExitCodeError, HttpError, ProblemDetails};
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// An error annotated with a message describing what was attempted when it occurred.
+///
+/// The wrapped error is kept as [`Error::source`] and decides the HTTP status through
+/// [`HttpError`], hence annotating an error never changes how it is presented to clients.
+///
+/// A backtrace is captured on creation if enabled through `RUST_BACKTRACE` or
+/// `RUST_LIB_BACKTRACE`, and ends up on this level of the error's [`Report`](crate::Report).
+#[derive(Debug)]
+pub struct Context<E> {
+	message: Cow<'static, str>,
+	source: E,
+	backtrace: Backtrace,
+}
+
+impl<E> Context<E> {
+	pub fn new(message: impl Into<Cow<'static, str>>, source: E) -> Context<E> {
+		Context {
+			message: message.into(),
+			source,
+			backtrace: Backtrace::capture(),
+		}
+	}
+
+	#[cfg(test)]
+	pub(crate) fn with_backtrace(mut self, backtrace: Backtrace) -> Context<E> {
+		self.backtrace = backtrace;
+		self
+	}
+
+	pub fn message(&self) -> &str {
+		&self.message
+	}
+
+	pub fn inner(&self) -> &E {
+		&self.source
+	}
+
+	pub fn into_inner(self) -> E {
+		self.source
+	}
+
+	pub fn backtrace(&self) -> Option<&Backtrace> {
+		(self.backtrace.status() == BacktraceStatus::Captured).then_some(&self.backtrace)
+	}
+}
+
+impl<E> Display for Context<E> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		if let Some(backtrace) = self.backtrace() {
+			offer_backtrace(backtrace);
+		}
+
+		f.write_str(&self.message)
+	}
+}
+
+impl<E: Error + 'static> Error for Context<E> {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		Some(&self.source)
+	}
+}
+
+impl<E: HttpError> HttpError for Context<E> {
+	fn problem_details(&self) -> ProblemDetails {
+		self.source.problem_details()
+	}
+}
+
+impl<E: ExitCodeError + 'static> ExitCodeError for Context<E> {
+	fn exit_code(&self) -> i32 {
+		self.source.exit_code()
+	}
+}
+
+pub trait ContextExt {
+	type V;
+	type E;
+
+	fn context(self, message: impl Into<Cow<'static, str>>) -> Result<Self::V, Context<Self::E>>;
+
+	fn with_context<M: Into<Cow<'static, str>>>(
+		self,
+		message: impl FnOnce() -> M,
+	) -> Result<Self::V, Context<Self::E>>;
+}
+
+impl<V, E: Error> ContextExt for Result<V, E> {
+	type V = V;
+	type E = E;
+
+	fn context(self, message: impl Into<Cow<'static, str>>) -> Result<V, Context<E>> {
+		self.map_err(|source| Context::new(message, source))
+	}
+
+	fn with_context<M: Into<Cow<'static, str>>>(
+		self,
+		message: impl FnOnce() -> M,
+	) -> Result<V, Context<E>> {
+		self.map_err(|source| Context::new(message(), source))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ErrorExt;
+	use http::StatusCode;
+	use thiserror::Error;
+
+	#[derive(Debug, Error)]
+	#[error("user not found")]
+	struct NotFoundError;
+
+	impl HttpError for NotFoundError {
+		fn problem_details(&self) -> ProblemDetails {
+			ProblemDetails::new(StatusCode::NOT_FOUND)
+		}
+	}
+
+	impl ExitCodeError for NotFoundError {
+		fn exit_code(&self) -> i32 {
+			66
+		}
+	}
+
+	#[test]
+	fn source_is_preserved() {
+		// Arrange
+		let res = Err::<(), _>(NotFoundError);
+
+		// Act
+		let res = res.with_context(|| format!("could not load user {}", 5));
+
+		// Assert
+		assert_eq!(
+			res.unwrap_err().to_pretty_string(),
+			"could not load user 5, caused by\nuser not found"
+		);
+	}
+
+	#[test]
+	fn status_of_source_is_kept() {
+		// Arrange
+		let res = Err::<(), _>(NotFoundError);
+
+		// Act
+		let res = res.context("could not load user");
+
+		// Assert
+		assert_eq!(
+			res.unwrap_err().problem_details().status(),
+			StatusCode::NOT_FOUND
+		);
+	}
+	#[test]
+	fn exit_code_of_source_is_kept() {
+		// Arrange
+		let res = Err::<(), _>(NotFoundError);
+
+		// Act
+		let res = res.context("could not load user");
+
+		// Assert
+		assert_eq!(res.unwrap_err().exit_code(), 66);
+	}
+}
@@ -1,3 +1,4 @@
+use crate::Report;
 use std::error::Error;
 
 pub trait ErrorExt: Error {
@@ -13,6 +14,10 @@ pub trait ErrorExt: Error {
 
 		res
 	}
+
+	fn to_report(&self) -> Report {
+		Report::new(self)
+	}
 }
 
 impl<E: ?Sized + Error> ErrorExt for E {}
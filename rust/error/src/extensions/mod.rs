@@ -1,7 +1,9 @@
+mod context;
 mod error;
 mod infallible_result;
 mod result;
 
+pub use context::*;
 pub use error::*;
 pub use infallible_result::*;
 pub use result::*;
@@ -1,14 +1,23 @@
 use crate::extensions::error::ErrorExt;
+use crate::ExitCodeError;
 use std::error::Error;
 use std::process::exit;
 
 pub trait ResultExt {
 	type V;
+	type E;
 	fn must(self) -> Self::V;
+
+	/// Like [`ResultExt::must`], but prints the error as a JSON [`Report`](crate::Report) and exits
+	/// with the code chosen by the error.
+	fn must_report(self) -> Self::V
+	where
+		Self::E: ExitCodeError;
 }
 
 impl<V, E: Error> ResultExt for Result<V, E> {
 	type V = V;
+	type E = E;
 	fn must(self) -> Self::V {
 		match self {
 			Ok(v) => v,
@@ -18,4 +27,17 @@ impl<V, E: Error> ResultExt for Result<V, E> {
 			}
 		}
 	}
+
+	fn must_report(self) -> Self::V
+	where
+		E: ExitCodeError,
+	{
+		match self {
+			Ok(v) => v,
+			Err(err) => {
+				eprintln!("{}", err.to_report().to_json());
+				exit(err.exit_code());
+			}
+		}
+	}
 }
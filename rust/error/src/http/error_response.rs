@@ -0,0 +1,102 @@
+use crate::{ErrorExt, HttpError, ProblemDetails};
+use axum::body::Body;
+use axum::http::HeaderValue;
+use axum::response::{IntoResponse, Response};
+use std::error::Error;
+use uuid::Uuid;
+
+pub const CORRELATION_ID_HEADER: &str = "x-correlation-id";
+
+/// Turns any [`HttpError`] into an axum response.
+///
+/// The source chain of the error is logged when the response is produced, at error level for
+/// server errors and debug level otherwise. The log line and the response share a correlation id,
+/// sent to the client both in the [`CORRELATION_ID_HEADER`] header and the problem details.
+pub struct ErrorResponse {
+	problem_details: ProblemDetails,
+	error: Box<dyn Error + Send>,
+}
+
+impl ErrorResponse {
+	pub fn new<E: HttpError + Error + Send + 'static>(error: E) -> ErrorResponse {
+		ErrorResponse {
+			problem_details: error.problem_details(),
+			error: Box::new(error),
+		}
+	}
+
+	pub fn problem_details(&self) -> &ProblemDetails {
+		&self.problem_details
+	}
+
+	pub fn error(&self) -> &(dyn Error + Send + 'static) {
+		self.error.as_ref()
+	}
+}
+
+impl<E: HttpError + Error + Send + 'static> From<E> for ErrorResponse {
+	fn from(error: E) -> Self {
+		ErrorResponse::new(error)
+	}
+}
+
+impl IntoResponse for ErrorResponse {
+	fn into_response(self) -> Response {
+		let correlation_id = Uuid::new_v4().to_string();
+		let status = self.problem_details.status();
+		let chain = self.error.to_pretty_string();
+
+		if status.is_server_error() {
+			tracing::error!(correlation_id, %status, "{chain}");
+		} else {
+			tracing::debug!(correlation_id, %status, "{chain}");
+		}
+
+		let mut response = self
+			.problem_details
+			.with_extension("correlation_id", &correlation_id)
+			.into_response::<Body>();
+
+		let correlation_id =
+			HeaderValue::try_from(correlation_id).expect("UUID should be a valid header value");
+		response
+			.headers_mut()
+			.insert(CORRELATION_ID_HEADER, correlation_id);
+
+		response
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use axum::http::StatusCode;
+	use thiserror::Error;
+
+	#[derive(Debug, Error)]
+	#[error("could not find user")]
+	struct NotFoundError;
+
+	impl HttpError for NotFoundError {
+		fn problem_details(&self) -> ProblemDetails {
+			ProblemDetails::new(StatusCode::NOT_FOUND)
+		}
+	}
+
+	#[test]
+	fn correlation_id_in_header_and_body() {
+		// Arrange
+		let error = ErrorResponse::from(NotFoundError);
+
+		// Act
+		let response = error.into_response();
+
+		// Assert
+		assert_eq!(response.status(), StatusCode::NOT_FOUND);
+		let header = response.headers().get(CORRELATION_ID_HEADER).unwrap();
+		let header = header.to_str().unwrap().to_string();
+		let body = futures::executor::block_on(axum::body::to_bytes(response.into_body(), 1024));
+		let body = serde_json::from_slice::<ProblemDetails>(&body.unwrap()).unwrap();
+		assert_eq!(body.extensions()["correlation_id"], header.as_str());
+	}
+}
@@ -1,3 +1,57 @@
-pub trait HttpError<Body> {
-	fn into_response(self) -> http::Response<Body>;
+use crate::ProblemDetails;
+
+/// An error which can be presented to HTTP clients.
+///
+/// Implementors pick the status and the public message per variant, while the serialisation into
+/// an `application/problem+json` response is shared.
+pub trait HttpError {
+	fn problem_details(&self) -> ProblemDetails;
+
+	fn into_response<Body: From<String>>(self) -> http::Response<Body>
+	where
+		Self: Sized,
+	{
+		self.problem_details().into_response()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use http::StatusCode;
+
+	enum UserError {
+		NotFound { id: u32 },
+		Database,
+	}
+
+	impl HttpError for UserError {
+		fn problem_details(&self) -> ProblemDetails {
+			match self {
+				UserError::NotFound { id } => ProblemDetails::new(StatusCode::NOT_FOUND)
+					.with_detail(format!("no user with id {id}"))
+					.with_extension("id", id),
+				UserError::Database => ProblemDetails::new(StatusCode::INTERNAL_SERVER_ERROR),
+			}
+		}
+	}
+
+	#[test]
+	fn status_per_variant() {
+		// Arrange
+		let not_found = UserError::NotFound { id: 5 };
+		let database = UserError::Database;
+
+		// Act
+		let not_found = not_found.into_response::<String>();
+		let database = database.into_response::<String>();
+
+		// Assert
+		assert_eq!(not_found.status(), StatusCode::NOT_FOUND);
+		assert_eq!(
+			not_found.body(),
+			r#"{"type":"about:blank","title":"Not Found","status":404,"detail":"no user with id 5","id":5}"#
+		);
+		assert_eq!(database.status(), StatusCode::INTERNAL_SERVER_ERROR);
+	}
 }
@@ -1,3 +1,9 @@
+#[cfg(feature = "axum")]
+mod error_response;
 mod http_error;
+mod problem_details;
 
+#[cfg(feature = "axum")]
+pub use error_response::*;
 pub use http_error::*;
+pub use problem_details::*;
@@ -0,0 +1,207 @@
+use http::header::CONTENT_TYPE;
+use http::{HeaderValue, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const PROBLEM_JSON_CONTENT_TYPE: &str = "application/problem+json";
+
+/// A problem details object as specified by [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807).
+///
+/// Everything set on it is sent to clients, hence it must only carry information fit for the
+/// public. Internal details belong in the source chain of the originating error.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProblemDetails {
+	#[serde(rename = "type", default = "about_blank")]
+	problem_type: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	title: Option<String>,
+	#[serde(with = "status_code")]
+	status: StatusCode,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	detail: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	instance: Option<String>,
+	#[serde(flatten)]
+	extensions: BTreeMap<String, serde_json::Value>,
+}
+
+impl ProblemDetails {
+	/// Creates a problem of type `about:blank`, titled by the canonical reason of `status`.
+	pub fn new(status: StatusCode) -> ProblemDetails {
+		ProblemDetails {
+			problem_type: about_blank(),
+			title: status.canonical_reason().map(String::from),
+			status,
+			detail: None,
+			instance: None,
+			extensions: BTreeMap::new(),
+		}
+	}
+
+	pub fn with_type(mut self, problem_type: impl Into<String>) -> ProblemDetails {
+		self.problem_type = problem_type.into();
+		self
+	}
+
+	pub fn with_title(mut self, title: impl Into<String>) -> ProblemDetails {
+		self.title = Some(title.into());
+		self
+	}
+
+	pub fn with_detail(mut self, detail: impl Into<String>) -> ProblemDetails {
+		self.detail = Some(detail.into());
+		self
+	}
+
+	pub fn with_instance(mut self, instance: impl Into<String>) -> ProblemDetails {
+		self.instance = Some(instance.into());
+		self
+	}
+
+	/// Adds an extension member, replacing any previous member with the same key.
+	///
+	/// # Panics
+	/// If `value` can not be represented as JSON, e.g. a map with non-string keys.
+	pub fn with_extension(
+		mut self,
+		key: impl Into<String>,
+		value: impl Serialize,
+	) -> ProblemDetails {
+		let value =
+			serde_json::to_value(value).expect("problem extension should be representable as JSON");
+		self.extensions.insert(key.into(), value);
+		self
+	}
+
+	pub fn problem_type(&self) -> &str {
+		&self.problem_type
+	}
+
+	pub fn title(&self) -> Option<&str> {
+		self.title.as_deref()
+	}
+
+	pub fn status(&self) -> StatusCode {
+		self.status
+	}
+
+	pub fn detail(&self) -> Option<&str> {
+		self.detail.as_deref()
+	}
+
+	pub fn instance(&self) -> Option<&str> {
+		self.instance.as_deref()
+	}
+
+	pub fn extensions(&self) -> &BTreeMap<String, serde_json::Value> {
+		&self.extensions
+	}
+
+	pub fn to_json(&self) -> String {
+		serde_json::to_string(self).expect("problem details should be serializable to JSON")
+	}
+
+	pub fn into_response<Body: From<String>>(self) -> http::Response<Body> {
+		let mut response = http::Response::new(Body::from(self.to_json()));
+		*response.status_mut() = self.status;
+		response.headers_mut().insert(
+			CONTENT_TYPE,
+			HeaderValue::from_static(PROBLEM_JSON_CONTENT_TYPE),
+		);
+
+		response
+	}
+}
+
+fn about_blank() -> String {
+	String::from("about:blank")
+}
+
+mod status_code {
+	use http::StatusCode;
+	use serde::de::Error;
+	use serde::{Deserialize, Deserializer, Serializer};
+
+	pub fn serialize<S: Serializer>(status: &StatusCode, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_u16(status.as_u16())
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<StatusCode, D::Error> {
+		let status = u16::deserialize(deserializer)?;
+		StatusCode::from_u16(status).map_err(D::Error::custom)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn defaults_to_about_blank() {
+		// Arrange
+		let problem = ProblemDetails::new(StatusCode::NOT_FOUND);
+
+		// Act
+		let json = serde_json::to_value(&problem).unwrap();
+
+		// Assert
+		assert_eq!(
+			json,
+			json!({
+				"type": "about:blank",
+				"title": "Not Found",
+				"status": 404,
+			})
+		);
+	}
+
+	#[test]
+	fn extensions_are_flattened() {
+		// Arrange
+		let problem = ProblemDetails::new(StatusCode::FORBIDDEN)
+			.with_type("https://example.com/probs/out-of-credit")
+			.with_title("You do not have enough credit.")
+			.with_detail("Your current balance is 30, but that costs 50.")
+			.with_instance("/account/12345/msgs/abc")
+			.with_extension("balance", 30);
+
+		// Act
+		let json = serde_json::to_value(&problem).unwrap();
+		let deserialized = serde_json::from_value::<ProblemDetails>(json.clone()).unwrap();
+
+		// Assert
+		assert_eq!(
+			json,
+			json!({
+				"type": "https://example.com/probs/out-of-credit",
+				"title": "You do not have enough credit.",
+				"status": 403,
+				"detail": "Your current balance is 30, but that costs 50.",
+				"instance": "/account/12345/msgs/abc",
+				"balance": 30,
+			})
+		);
+		assert_eq!(deserialized, problem);
+	}
+
+	#[test]
+	fn response() {
+		// Arrange
+		let problem = ProblemDetails::new(StatusCode::CONFLICT);
+
+		// Act
+		let response = problem.into_response::<String>();
+
+		// Assert
+		assert_eq!(response.status(), StatusCode::CONFLICT);
+		assert_eq!(
+			response.headers().get(CONTENT_TYPE).unwrap(),
+			PROBLEM_JSON_CONTENT_TYPE
+		);
+		assert_eq!(
+			response.body(),
+			r#"{"type":"about:blank","title":"Conflict","status":409}"#
+		);
+	}
+}
@@ -1,5 +1,7 @@
 mod extensions;
 mod http;
+mod report;
 
 pub use extensions::*;
 pub use http::*;
+pub use report::*;
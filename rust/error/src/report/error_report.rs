@@ -0,0 +1,178 @@
+use serde::Serialize;
+use std::any::type_name_of_val;
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::error::Error;
+
+thread_local! {
+	/// Set while a [`Report`] formats an entry, to pick up the backtrace of that entry.
+	static OFFERED_BACKTRACE: RefCell<Option<Option<String>>> = const { RefCell::new(None) };
+}
+
+/// Hands the backtrace of an error to the [`Report`] currently formatting it, if any.
+///
+/// Errors are type erased along the chain and [`Error`] can't provide a backtrace on stable
+/// Rust, hence errors carrying one offer it from their `Display` implementation instead.
+pub(crate) fn offer_backtrace(backtrace: &Backtrace) {
+	OFFERED_BACKTRACE.with_borrow_mut(|offered| {
+		if let Some(offered @ None) = offered {
+			*offered = Some(backtrace.to_string());
+		}
+	});
+}
+
+/// The chain of an error captured as data, outermost error first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Report {
+	pub chain: Vec<ReportEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReportEntry {
+	pub message: String,
+	/// Only known for the outermost error, as sources are type erased.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub type_name: Option<&'static str>,
+	/// Only known for errors capturing one, such as [`Context`](crate::Context).
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub backtrace: Option<String>,
+}
+
+impl ReportEntry {
+	fn new(error: &(impl ?Sized + Error), type_name: Option<&'static str>) -> ReportEntry {
+		OFFERED_BACKTRACE.set(Some(None));
+		let message = error.to_string();
+		let backtrace = OFFERED_BACKTRACE.take().flatten();
+
+		ReportEntry {
+			message,
+			type_name,
+			backtrace,
+		}
+	}
+}
+
+impl Report {
+	pub fn new<E: ?Sized + Error>(error: &E) -> Report {
+		let type_name = Some(type_name_of_val(error)).filter(|name| !name.starts_with("dyn "));
+
+		let mut chain = vec![ReportEntry::new(error, type_name)];
+
+		let mut source = error.source();
+		while let Some(e) = source {
+			chain.push(ReportEntry::new(e, None));
+			source = e.source();
+		}
+
+		Report { chain }
+	}
+
+	pub fn to_json(&self) -> String {
+		serde_json::to_string(self).expect("report should be serializable to JSON")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Context, ErrorExt};
+	use serde_json::json;
+	use thiserror::Error;
+
+	#[derive(Debug, Error)]
+	#[error("could not load config")]
+	struct ConfigError {
+		source: std::io::Error,
+	}
+
+	#[test]
+	fn chain() {
+		// Arrange
+		let source = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+		let err = ConfigError { source };
+
+		// Act
+		let report = err.to_report();
+
+		// Assert
+		assert_eq!(report.chain.len(), 2);
+		assert_eq!(report.chain[0].message, "could not load config");
+		assert_eq!(
+			report.chain[0].type_name,
+			Some(std::any::type_name::<ConfigError>())
+		);
+		assert_eq!(report.chain[1].message, "no such file");
+		assert_eq!(report.chain[1].type_name, None);
+		assert!(report.chain.iter().all(|entry| entry.backtrace.is_none()));
+	}
+
+	#[test]
+	fn backtrace_of_context() {
+		// Arrange
+		let source = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+		let err = ConfigError { source };
+		let err = Context::new("could not start", err).with_backtrace(Backtrace::force_capture());
+
+		// Act
+		let report = err.to_report();
+
+		// Assert
+		assert_eq!(report.chain.len(), 3);
+		assert_eq!(report.chain[0].message, "could not start");
+		assert!(report.chain[0]
+			.backtrace
+			.as_ref()
+			.is_some_and(|backtrace| backtrace.contains("backtrace_of_context")));
+		assert_eq!(report.chain[1].backtrace, None);
+		assert_eq!(report.chain[2].backtrace, None);
+	}
+
+	#[test]
+	fn backtrace_of_nested_context() {
+		// Arrange
+		let source = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+		let err = Context::new("could not read", source).with_backtrace(Backtrace::force_capture());
+		let err = Context::new("could not start", err).with_backtrace(Backtrace::disabled());
+
+		// Act
+		let report = err.to_report();
+
+		// Assert
+		assert_eq!(report.chain[0].backtrace, None);
+		assert!(report.chain[1].backtrace.is_some());
+		assert_eq!(report.chain[2].backtrace, None);
+	}
+
+	#[test]
+	fn json() {
+		// Arrange
+		let report = Report {
+			chain: vec![
+				ReportEntry {
+					message: String::from("foo"),
+					type_name: Some("my::Error"),
+					backtrace: None,
+				},
+				ReportEntry {
+					message: String::from("bar"),
+					type_name: None,
+					backtrace: Some(String::from("0: main")),
+				},
+			],
+		};
+
+		// Act
+		let json = serde_json::from_str::<serde_json::Value>(&report.to_json()).unwrap();
+
+		// Assert
+		assert_eq!(
+			json,
+			json!({
+				"chain": [
+					{ "message": "foo", "type_name": "my::Error" },
+					{ "message": "bar", "backtrace": "0: main" },
+				]
+			})
+		);
+	}
+}
@@ -0,0 +1,9 @@
+use std::error::Error;
+
+/// An error which determines the code a process exits with when failing due to it.
+///
+/// Implementing it per error type, and picking the code per variant, lets supervisors tell failure
+/// causes apart without parsing output.
+pub trait ExitCodeError: Error {
+	fn exit_code(&self) -> i32;
+}
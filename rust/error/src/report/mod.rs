@@ -0,0 +1,5 @@
+mod error_report;
+mod exit_code;
+
+pub use error_report::*;
+pub use exit_code::*;
@@ -0,0 +1,269 @@
+use std::time::Duration;
+
+/// Decides how long to wait before restarting a failed task.
+///
+/// Delays grow from `base` up to `limit` according to the chosen strategy. A task which ran for
+/// at least `reset_after` before failing is considered to have recovered, hence the delay starts
+/// over from `base`, as does the attempt count when `max_attempts` is set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackoffPolicy {
+	strategy: Strategy,
+	base: Duration,
+	limit: Duration,
+	factor: u32,
+	reset_after: Duration,
+	max_attempts: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+	Exponential,
+	Constant,
+	DecorrelatedJitter,
+	Fibonacci,
+}
+
+impl BackoffPolicy {
+	/// Multiplies the delay by `factor`, 2 unless configured, after every failure.
+	pub fn exponential() -> BackoffPolicy {
+		BackoffPolicy::new(Strategy::Exponential)
+	}
+
+	/// Always waits `delay`.
+	pub fn constant(delay: Duration) -> BackoffPolicy {
+		BackoffPolicy::new(Strategy::Constant).with_base(delay)
+	}
+
+	/// Waits a random delay between `base` and three times the previous delay, spreading out
+	/// restarts of tasks failing at the same time.
+	pub fn decorrelated_jitter() -> BackoffPolicy {
+		BackoffPolicy::new(Strategy::DecorrelatedJitter)
+	}
+
+	/// Grows the delay along the Fibonacci sequence, i.e. slower than [`BackoffPolicy::exponential`].
+	pub fn fibonacci() -> BackoffPolicy {
+		BackoffPolicy::new(Strategy::Fibonacci)
+	}
+
+	fn new(strategy: Strategy) -> BackoffPolicy {
+		BackoffPolicy {
+			strategy,
+			base: Duration::from_millis(100),
+			limit: Duration::from_secs(60),
+			factor: 2,
+			reset_after: Duration::from_secs(5 * 60),
+			max_attempts: None,
+		}
+	}
+
+	pub fn with_base(mut self, base: Duration) -> BackoffPolicy {
+		self.base = base;
+		self
+	}
+
+	pub fn with_limit(mut self, limit: Duration) -> BackoffPolicy {
+		self.limit = limit;
+		self
+	}
+
+	/// Only used by [`BackoffPolicy::exponential`].
+	pub fn with_factor(mut self, factor: u32) -> BackoffPolicy {
+		self.factor = factor;
+		self
+	}
+
+	pub fn with_reset_after(mut self, reset_after: Duration) -> BackoffPolicy {
+		self.reset_after = reset_after;
+		self
+	}
+
+	/// Gives up after `max_attempts` consecutive failures, i.e. restarts at most `max_attempts - 1`
+	/// times in a row.
+	pub fn with_max_attempts(mut self, max_attempts: u32) -> BackoffPolicy {
+		self.max_attempts = Some(max_attempts);
+		self
+	}
+
+	pub(crate) fn backoff(&self) -> Backoff {
+		Backoff {
+			policy: self.clone(),
+			previous: Duration::ZERO,
+			current: self.base,
+			attempts: 0,
+		}
+	}
+}
+
+impl Default for BackoffPolicy {
+	fn default() -> Self {
+		BackoffPolicy::exponential()
+	}
+}
+
+/// The state of a [`BackoffPolicy`] applied to one task.
+#[derive(Debug, Clone)]
+pub(crate) struct Backoff {
+	policy: BackoffPolicy,
+	previous: Duration,
+	current: Duration,
+	attempts: u32,
+}
+
+impl Backoff {
+	/// Registers a failure of a task which ran for `ran_for`, giving the delay before restarting it
+	/// or `None` if attempts are exhausted.
+	pub(crate) fn next(&mut self, ran_for: Duration) -> Option<Duration> {
+		if ran_for >= self.policy.reset_after {
+			*self = self.policy.backoff();
+			self.attempts = 1;
+			return self.allows_attempt().then_some(self.policy.base);
+		}
+
+		self.attempts += 1;
+		if !self.allows_attempt() {
+			return None;
+		}
+
+		let BackoffPolicy {
+			strategy,
+			base,
+			limit,
+			factor,
+			..
+		} = self.policy;
+
+		let next = match strategy {
+			Strategy::Exponential => self.current.saturating_mul(factor),
+			Strategy::Constant => base,
+			Strategy::DecorrelatedJitter => {
+				let upper = self.current.saturating_mul(3).max(base);
+				let millis = fastrand::u64(millis(base)..=millis(upper));
+				Duration::from_millis(millis)
+			}
+			Strategy::Fibonacci => self.previous.saturating_add(self.current),
+		};
+
+		self.previous = self.current;
+		self.current = next.min(limit);
+
+		Some(self.current)
+	}
+
+	fn allows_attempt(&self) -> bool {
+		self.policy
+			.max_attempts
+			.is_none_or(|max_attempts| self.attempts < max_attempts)
+	}
+}
+
+fn millis(duration: Duration) -> u64 {
+	u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn delays(policy: BackoffPolicy, n: usize) -> Vec<Duration> {
+		let mut backoff = policy.backoff();
+		(0..n).map_while(|_| backoff.next(Duration::ZERO)).collect()
+	}
+
+	fn secs(secs: &[u64]) -> Vec<Duration> {
+		secs.iter().copied().map(Duration::from_secs).collect()
+	}
+
+	#[test]
+	fn exponential() {
+		// Arrange
+		let policy = BackoffPolicy::exponential()
+			.with_base(Duration::from_secs(1))
+			.with_limit(Duration::from_secs(20))
+			.with_factor(3);
+
+		// Act
+		let delays = delays(policy, 4);
+
+		// Assert
+		assert_eq!(delays, secs(&[3, 9, 20, 20]));
+	}
+
+	#[test]
+	fn constant() {
+		// Arrange
+		let policy = BackoffPolicy::constant(Duration::from_secs(2));
+
+		// Act
+		let delays = delays(policy, 3);
+
+		// Assert
+		assert_eq!(delays, secs(&[2, 2, 2]));
+	}
+
+	#[test]
+	fn fibonacci() {
+		// Arrange
+		let policy = BackoffPolicy::fibonacci()
+			.with_base(Duration::from_secs(1))
+			.with_limit(Duration::from_secs(10));
+
+		// Act
+		let delays = delays(policy, 6);
+
+		// Assert
+		assert_eq!(delays, secs(&[1, 2, 3, 5, 8, 10]));
+	}
+
+	#[test]
+	fn decorrelated_jitter_stays_within_bounds() {
+		// Arrange
+		let base = Duration::from_secs(1);
+		let limit = Duration::from_secs(30);
+		let policy = BackoffPolicy::decorrelated_jitter()
+			.with_base(base)
+			.with_limit(limit);
+
+		// Act
+		let delays = delays(policy, 100);
+
+		// Assert
+		let mut previous = base;
+		for delay in delays {
+			assert!(base <= delay && delay <= (previous * 3).min(limit));
+			previous = delay;
+		}
+	}
+
+	#[test]
+	fn max_attempts() {
+		// Arrange
+		let policy = BackoffPolicy::constant(Duration::from_secs(1)).with_max_attempts(3);
+
+		// Act
+		let delays = delays(policy, 5);
+
+		// Assert
+		assert_eq!(delays, secs(&[1, 1]));
+	}
+
+	#[test]
+	fn long_run_resets() {
+		// Arrange
+		let policy = BackoffPolicy::exponential()
+			.with_base(Duration::from_secs(1))
+			.with_max_attempts(2);
+		let mut backoff = policy.backoff();
+
+		// Act
+		let first = backoff.next(Duration::ZERO);
+		let exhausted = backoff.next(Duration::ZERO);
+		let after_reset = backoff.next(Duration::from_secs(5 * 60));
+		let exhausted_after_reset = backoff.next(Duration::ZERO);
+
+		// Assert
+		assert_eq!(first, Some(Duration::from_secs(2)));
+		assert_eq!(exhausted, None);
+		assert_eq!(after_reset, Some(Duration::from_secs(1)));
+		assert_eq!(exhausted_after_reset, None);
+	}
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Limits the number of concurrent calls per key, e.g. per endpoint or per account.
+///
+/// Keys without calls in flight take up no memory.
+#[derive(Debug)]
+pub struct KeyedConcurrencyLimiter<K> {
+	limit: usize,
+	semaphores: Arc<Mutex<HashMap<K, Arc<Semaphore>>>>,
+}
+
+impl<K: Eq + Hash + Clone> KeyedConcurrencyLimiter<K> {
+	/// # Panics
+	/// If `limit` is zero.
+	pub fn new(limit: usize) -> KeyedConcurrencyLimiter<K> {
+		assert!(limit > 0, "concurrency limit should not be zero");
+
+		KeyedConcurrencyLimiter {
+			limit,
+			semaphores: Arc::new(Mutex::new(HashMap::new())),
+		}
+	}
+
+	/// Waits until fewer than `limit` permits for `key` are held and takes one.
+	pub async fn acquire(&self, key: K) -> KeyedPermit<K> {
+		let semaphore = {
+			let mut semaphores = self.semaphores.lock().expect("lock not poisoned");
+			semaphores
+				.entry(key.clone())
+				.or_insert_with(|| Arc::new(Semaphore::new(self.limit)))
+				.clone()
+		};
+
+		// forgets the key if the wait is cancelled as well
+		let release = KeyRelease {
+			key,
+			semaphore,
+			semaphores: self.semaphores.clone(),
+		};
+
+		let permit = release
+			.semaphore
+			.clone()
+			.acquire_owned()
+			.await
+			.expect("semaphore is never closed");
+
+		KeyedPermit {
+			_permit: permit,
+			release,
+		}
+	}
+
+	/// Runs `fut` while holding a permit for `key`.
+	pub async fn run<F: Future>(&self, key: K, fut: F) -> F::Output {
+		let _permit = self.acquire(key).await;
+		fut.await
+	}
+
+	/// The number of keys with calls in flight or waiting.
+	pub fn active_keys(&self) -> usize {
+		self.semaphores.lock().expect("lock not poisoned").len()
+	}
+}
+
+/// Frees its slot for the key once dropped.
+#[derive(Debug)]
+pub struct KeyedPermit<K: Eq + Hash> {
+	// dropped before the release, which only forgets the key once no permits are left
+	_permit: OwnedSemaphorePermit,
+	release: KeyRelease<K>,
+}
+
+impl<K: Eq + Hash> KeyedPermit<K> {
+	pub fn key(&self) -> &K {
+		&self.release.key
+	}
+}
+
+/// Forgets the key once dropped by its last holder or waiter.
+#[derive(Debug)]
+struct KeyRelease<K: Eq + Hash> {
+	key: K,
+	semaphore: Arc<Semaphore>,
+	semaphores: Arc<Mutex<HashMap<K, Arc<Semaphore>>>>,
+}
+
+impl<K: Eq + Hash> Drop for KeyRelease<K> {
+	fn drop(&mut self) {
+		// the map and this release are the only owners, hence nobody holds or waits for the key
+		let mut semaphores = self.semaphores.lock().expect("lock not poisoned");
+		if Arc::strong_count(&self.semaphore) == 2 {
+			semaphores.remove(&self.key);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use std::time::Duration;
+	use tokio::time::Instant;
+
+	#[tokio::test(start_paused = true)]
+	async fn limits_calls_per_key() {
+		// Arrange
+		let limiter = Arc::new(KeyedConcurrencyLimiter::new(2));
+		let in_flight = Arc::new(AtomicUsize::new(0));
+		let max_in_flight = Arc::new(AtomicUsize::new(0));
+		let start = Instant::now();
+
+		// Act
+		let handles = (0..6)
+			.map(|_| {
+				let limiter = limiter.clone();
+				let in_flight = in_flight.clone();
+				let max_in_flight = max_in_flight.clone();
+				tokio::spawn(async move {
+					limiter
+						.run("institutions", async {
+							let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+							max_in_flight.fetch_max(current, Ordering::SeqCst);
+							tokio::time::sleep(Duration::from_secs(1)).await;
+							in_flight.fetch_sub(1, Ordering::SeqCst);
+						})
+						.await
+				})
+			})
+			.collect::<Vec<_>>();
+		for handle in handles {
+			handle.await.unwrap();
+		}
+
+		// Assert
+		assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+		assert_eq!(start.elapsed(), Duration::from_secs(3));
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn keys_are_independent() {
+		// Arrange
+		let limiter = KeyedConcurrencyLimiter::new(1);
+		let start = Instant::now();
+
+		// Act
+		tokio::join!(
+			limiter.run("accounts", tokio::time::sleep(Duration::from_secs(1))),
+			limiter.run("institutions", tokio::time::sleep(Duration::from_secs(1))),
+		);
+
+		// Assert
+		assert_eq!(start.elapsed(), Duration::from_secs(1));
+	}
+
+	#[tokio::test]
+	async fn forgets_idle_keys() {
+		// Arrange
+		let limiter = KeyedConcurrencyLimiter::new(1);
+		let permit = limiter.acquire("accounts").await;
+		let active_while_held = limiter.active_keys();
+
+		// Act
+		drop(permit);
+
+		// Assert
+		assert_eq!(active_while_held, 1);
+		assert_eq!(limiter.active_keys(), 0);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn forgets_keys_of_cancelled_waiters() {
+		// Arrange
+		let limiter = KeyedConcurrencyLimiter::new(1);
+		let permit = limiter.acquire("accounts").await;
+		let mut waiter = Box::pin(limiter.acquire("accounts"));
+		assert!(futures::poll!(&mut waiter).is_pending());
+
+		// Act
+		drop(permit);
+		let active_while_waiting = limiter.active_keys();
+		drop(waiter);
+
+		// Assert
+		assert_eq!(active_while_waiting, 1);
+		assert_eq!(limiter.active_keys(), 0);
+	}
+}
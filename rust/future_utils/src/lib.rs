@@ -1,2 +1,16 @@
+mod backoff;
+mod concurrency_limiter;
 pub mod extensions;
+mod rate_limiter;
 mod restart;
+mod shutdown;
+mod single_flight;
+mod supervisor;
+
+pub use backoff::*;
+pub use concurrency_limiter::*;
+pub use rate_limiter::*;
+pub use restart::*;
+pub use shutdown::*;
+pub use single_flight::*;
+pub use supervisor::*;
@@ -0,0 +1,165 @@
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// An async token bucket, letting through `capacity` calls per `period` on average and bursts of
+/// up to `capacity` calls.
+///
+/// Callers waiting for a token are served in the order they started waiting in.
+#[derive(Debug)]
+pub struct RateLimiter {
+	capacity: u32,
+	interval: Duration,
+	bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+	available: u32,
+	refilled_at: Instant,
+}
+
+impl RateLimiter {
+	/// Creates a full bucket.
+	///
+	/// # Panics
+	/// If `capacity` or `period` is zero.
+	pub fn new(capacity: u32, period: Duration) -> RateLimiter {
+		assert!(capacity > 0, "rate limiter capacity should not be zero");
+		assert!(!period.is_zero(), "rate limiter period should not be zero");
+
+		RateLimiter {
+			capacity,
+			interval: period / capacity,
+			bucket: Mutex::new(Bucket {
+				available: capacity,
+				refilled_at: Instant::now(),
+			}),
+		}
+	}
+
+	/// Waits until a token is available and takes it.
+	pub async fn acquire(&self) {
+		let mut bucket = self.bucket.lock().await;
+
+		loop {
+			self.refill(&mut bucket);
+			if bucket.available > 0 {
+				bucket.available -= 1;
+				return;
+			}
+
+			tokio::time::sleep_until(bucket.refilled_at + self.interval).await;
+		}
+	}
+
+	/// Takes a token if one is available without waiting.
+	pub fn try_acquire(&self) -> bool {
+		let Ok(mut bucket) = self.bucket.try_lock() else {
+			return false;
+		};
+
+		self.refill(&mut bucket);
+		if bucket.available == 0 {
+			return false;
+		}
+
+		bucket.available -= 1;
+		true
+	}
+
+	/// Runs `fut` once a token is available.
+	pub async fn run<F: Future>(&self, fut: F) -> F::Output {
+		self.acquire().await;
+		fut.await
+	}
+
+	fn refill(&self, bucket: &mut Bucket) {
+		let now = Instant::now();
+		let elapsed = now.duration_since(bucket.refilled_at);
+		let refilled = elapsed.as_nanos() / self.interval.as_nanos();
+		let refilled = u32::try_from(refilled).unwrap_or(u32::MAX);
+
+		bucket.available = bucket.available.saturating_add(refilled);
+		if bucket.available >= self.capacity {
+			bucket.available = self.capacity;
+			bucket.refilled_at = now;
+		} else {
+			bucket.refilled_at += self.interval * refilled;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::Arc;
+
+	#[tokio::test(start_paused = true)]
+	async fn lets_bursts_through() {
+		// Arrange
+		let limiter = RateLimiter::new(3, Duration::from_secs(3));
+		let start = Instant::now();
+
+		// Act
+		for _ in 0..3 {
+			limiter.acquire().await;
+		}
+
+		// Assert
+		assert_eq!(start.elapsed(), Duration::ZERO);
+		assert!(!limiter.try_acquire());
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn paces_calls_after_burst() {
+		// Arrange
+		let limiter = RateLimiter::new(2, Duration::from_secs(2));
+		let start = Instant::now();
+
+		// Act
+		for _ in 0..5 {
+			limiter.acquire().await;
+		}
+
+		// Assert
+		assert_eq!(start.elapsed(), Duration::from_secs(3));
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn refills_up_to_capacity() {
+		// Arrange
+		let limiter = RateLimiter::new(2, Duration::from_secs(2));
+		limiter.acquire().await;
+		limiter.acquire().await;
+
+		// Act
+		tokio::time::sleep(Duration::from_secs(60)).await;
+		let acquired = (0..3).filter(|_| limiter.try_acquire()).count();
+
+		// Assert
+		assert_eq!(acquired, 2);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn serves_concurrent_callers() {
+		// Arrange
+		let limiter = Arc::new(RateLimiter::new(1, Duration::from_secs(1)));
+		let start = Instant::now();
+
+		// Act
+		let handles = (0..4)
+			.map(|i| {
+				let limiter = limiter.clone();
+				tokio::spawn(async move { limiter.run(async move { i }).await })
+			})
+			.collect::<Vec<_>>();
+		for handle in handles {
+			handle.await.unwrap();
+		}
+
+		// Assert
+		assert_eq!(start.elapsed(), Duration::from_secs(3));
+	}
+}
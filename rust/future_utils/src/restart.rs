@@ -1,21 +1,47 @@
+use crate::BackoffPolicy;
 use std::future::Future;
 use std::time::Duration;
 use tokio::time::Instant;
 
-const BACKOFF_BASE: Duration = Duration::from_millis(100);
-const BACKOFF_LIMIT: Duration = Duration::from_secs(60);
-const BACKOFF_SCALING_FACTOR: u32 = 2;
-const BACKOFF_RESET_AFTER: Duration = Duration::from_secs(5 * 60);
-
 pub async fn restart_on_failure_with_backoff<
 	Output,
 	Error,
 	F: Future<Output = Result<Output, Error>>,
 >(
+	fut_constructor: impl FnMut() -> F,
+	on_error: impl FnMut(Duration, Error),
+) -> Output {
+	let res = restart_on_failure_with_policy(
+		&BackoffPolicy::default(),
+		fut_constructor,
+		|_| true,
+		on_error,
+	)
+	.await;
+
+	match res {
+		Ok(output) => output,
+		Err(_) => unreachable!("default policy should retry indefinitely"),
+	}
+}
+
+/// Restarts the future constructed by `fut_constructor` until it succeeds, waiting between attempts
+/// as decided by `policy`.
+///
+/// Errors for which `should_retry` returns false, and errors occurring once `policy` has run out of
+/// attempts, are returned as is. Every other error is handed to `on_error` along with the delay
+/// before the next attempt.
+pub async fn restart_on_failure_with_policy<
+	Output,
+	Error,
+	F: Future<Output = Result<Output, Error>>,
+>(
+	policy: &BackoffPolicy,
 	mut fut_constructor: impl FnMut() -> F,
+	mut should_retry: impl FnMut(&Error) -> bool,
 	mut on_error: impl FnMut(Duration, Error),
-) -> Output {
-	let mut backoff = BACKOFF_BASE;
+) -> Result<Output, Error> {
+	let mut backoff = policy.backoff();
 
 	loop {
 		let start = Instant::now();
@@ -23,26 +49,28 @@ pub async fn restart_on_failure_with_backoff<
 		let time_elapsed = start.elapsed();
 
 		let error = match res {
-			Ok(res) => return res,
+			Ok(res) => return Ok(res),
 			Err(error) => error,
 		};
 
-		if time_elapsed < BACKOFF_RESET_AFTER {
-			backoff *= BACKOFF_SCALING_FACTOR;
-			backoff = backoff.min(BACKOFF_LIMIT);
-		} else {
-			backoff = BACKOFF_BASE;
+		if !should_retry(&error) {
+			return Err(error);
 		}
 
-		on_error(backoff, error);
+		let Some(delay) = backoff.next(time_elapsed) else {
+			return Err(error);
+		};
+
+		on_error(delay, error);
 
-		tokio::time::sleep(backoff).await;
+		tokio::time::sleep(delay).await;
 	}
 }
 
 #[cfg(test)]
 mod tests {
-	use crate::restart::restart_on_failure_with_backoff;
+	use crate::restart::{restart_on_failure_with_backoff, restart_on_failure_with_policy};
+	use crate::BackoffPolicy;
 	use std::cell::RefCell;
 	use std::time::Duration;
 	use tokio::time::Instant;
@@ -92,4 +120,51 @@ mod tests {
 		let expected_cumulative_time_slept = Duration::from_millis(8083 * 1000 + 400);
 		assert_eq!(elapsed_time, expected_cumulative_time_slept);
 	}
+
+	#[tokio::test(start_paused = true)]
+	async fn gives_up_after_max_attempts() {
+		// Arrange
+		let policy = BackoffPolicy::constant(Duration::from_secs(1)).with_max_attempts(2);
+		let count = RefCell::new(0);
+		let task = || async {
+			*count.borrow_mut() += 1;
+			Err::<(), _>(*count.borrow())
+		};
+
+		// Act
+		let mut errors = Vec::new();
+		let res =
+			restart_on_failure_with_policy(&policy, task, |_| true, |_, err| errors.push(err))
+				.await;
+
+		// Assert
+		assert_eq!(*count.borrow(), 2);
+		assert_eq!(errors, vec![1]);
+		assert_eq!(res, Err(2));
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn does_not_retry_rejected_errors() {
+		// Arrange
+		let policy = BackoffPolicy::default();
+		let count = RefCell::new(0);
+		let task = || async {
+			*count.borrow_mut() += 1;
+			Err::<(), _>(*count.borrow())
+		};
+
+		// Act
+		let mut errors = Vec::new();
+		let res = restart_on_failure_with_policy(
+			&policy,
+			task,
+			|err| *err < 3,
+			|_, err| errors.push(err),
+		)
+		.await;
+
+		// Assert
+		assert_eq!(errors, vec![1, 2]);
+		assert_eq!(res, Err(3));
+	}
 }
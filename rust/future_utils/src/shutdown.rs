@@ -0,0 +1,257 @@
+use crate::extensions::CancellationTokenExt;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio_util::sync::{CancellationToken, WaitForCancellationFutureOwned};
+
+/// Coordinates a graceful shutdown of a process.
+///
+/// Shutting down is triggered by cancelling the root token, either explicitly or, after
+/// [`Shutdown::cancel_on_signals`], by SIGINT or SIGTERM. Tasks registered through
+/// [`Shutdown::track`] or [`Shutdown::spawn`] are then given the drain deadline to finish, after
+/// which [`Shutdown::drain`] reports the ones which did not.
+pub struct Shutdown {
+	token: CancellationToken,
+	drain_deadline: Duration,
+	tracked: Arc<Tracked>,
+}
+
+struct Tracked {
+	next_id: AtomicU64,
+	tasks: watch::Sender<BTreeMap<u64, String>>,
+}
+
+impl Shutdown {
+	pub fn new() -> Shutdown {
+		Shutdown {
+			token: CancellationToken::new(),
+			drain_deadline: Duration::from_secs(30),
+			tracked: Arc::new(Tracked {
+				next_id: AtomicU64::new(0),
+				tasks: watch::Sender::new(BTreeMap::new()),
+			}),
+		}
+	}
+
+	pub fn with_drain_deadline(mut self, drain_deadline: Duration) -> Shutdown {
+		self.drain_deadline = drain_deadline;
+		self
+	}
+
+	/// Cancels the root token once the process receives SIGINT or, on unix, SIGTERM.
+	///
+	/// # Panics
+	/// If called outside of a tokio runtime or if the signal handlers can not be registered.
+	pub fn cancel_on_signals(self) -> Shutdown {
+		let token = self.token.clone();
+		tokio::spawn(async move {
+			token
+				.run_until_cancelled(token.cancel_when_done(signal()))
+				.await
+		});
+
+		self
+	}
+
+	/// The root token, cancelled when shutting down.
+	pub fn token(&self) -> &CancellationToken {
+		&self.token
+	}
+
+	/// Completes once shutting down is triggered, e.g. for `axum::serve(..).with_graceful_shutdown`.
+	pub fn cancelled(&self) -> WaitForCancellationFutureOwned {
+		self.token.clone().cancelled_owned()
+	}
+
+	/// Registers `fut` as a task named `name`, which is expected to finish within the drain deadline
+	/// once shutting down.
+	pub fn track<F: Future>(
+		&self,
+		name: impl Into<String>,
+		fut: F,
+	) -> impl Future<Output = F::Output> {
+		let id = self.tracked.next_id.fetch_add(1, Ordering::Relaxed);
+		self.tracked.tasks.send_modify(|tasks| {
+			tasks.insert(id, name.into());
+		});
+
+		let guard = TrackGuard {
+			id,
+			tracked: self.tracked.clone(),
+		};
+
+		async move {
+			let res = fut.await;
+			drop(guard);
+			res
+		}
+	}
+
+	/// Spawns `fut` as a task tracked by [`Shutdown::track`].
+	pub fn spawn<F>(&self, name: impl Into<String>, fut: F) -> JoinHandle<F::Output>
+	where
+		F: Future + Send + 'static,
+		F::Output: Send + 'static,
+	{
+		tokio::spawn(self.track(name.into(), fut))
+	}
+
+	/// Waits until either shutting down is triggered or every tracked task finished on its own, then
+	/// gives the remaining tracked tasks the drain deadline to finish.
+	pub async fn drain(self) -> ShutdownReport {
+		let mut tasks = self.tracked.tasks.subscribe();
+
+		tokio::select! {
+			_ = self.token.cancelled() => {},
+			_ = tasks.wait_for(BTreeMap::is_empty) => {},
+		}
+		self.token.cancel();
+
+		let _ = tokio::time::timeout(self.drain_deadline, tasks.wait_for(BTreeMap::is_empty)).await;
+
+		let unfinished = tasks.borrow().values().cloned().collect();
+		ShutdownReport {
+			drain_deadline: self.drain_deadline,
+			unfinished,
+		}
+	}
+}
+
+impl Default for Shutdown {
+	fn default() -> Self {
+		Shutdown::new()
+	}
+}
+
+struct TrackGuard {
+	id: u64,
+	tracked: Arc<Tracked>,
+}
+
+impl Drop for TrackGuard {
+	fn drop(&mut self) {
+		self.tracked.tasks.send_modify(|tasks| {
+			tasks.remove(&self.id);
+		});
+	}
+}
+
+#[cfg(unix)]
+async fn signal() {
+	use tokio::signal::unix::{signal, SignalKind};
+
+	let mut terminate =
+		signal(SignalKind::terminate()).expect("SIGTERM handler should be registrable");
+
+	tokio::select! {
+		res = tokio::signal::ctrl_c() => res.expect("SIGINT handler should be registrable"),
+		_ = terminate.recv() => {},
+	}
+}
+
+#[cfg(not(unix))]
+async fn signal() {
+	tokio::signal::ctrl_c()
+		.await
+		.expect("SIGINT handler should be registrable")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShutdownReport {
+	drain_deadline: Duration,
+	unfinished: Vec<String>,
+}
+
+impl ShutdownReport {
+	/// Whether every tracked task finished within the drain deadline.
+	pub fn is_clean(&self) -> bool {
+		self.unfinished.is_empty()
+	}
+
+	/// The names of the tracked tasks which did not finish within the drain deadline, in the order
+	/// they were registered in.
+	pub fn unfinished(&self) -> &[String] {
+		&self.unfinished
+	}
+}
+
+impl Display for ShutdownReport {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		if self.is_clean() {
+			return write!(f, "every task finished");
+		}
+
+		write!(
+			f,
+			"tasks did not finish within {:?}: {}",
+			self.drain_deadline,
+			self.unfinished.join(", ")
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test(start_paused = true)]
+	async fn reports_tasks_exceeding_drain_deadline() {
+		// Arrange
+		let shutdown = Shutdown::new().with_drain_deadline(Duration::from_secs(5));
+		let token = shutdown.token().clone();
+		shutdown.spawn("quick", {
+			let token = token.clone();
+			async move {
+				token.cancelled().await;
+				tokio::time::sleep(Duration::from_secs(1)).await;
+			}
+		});
+		shutdown.spawn("slow", {
+			let token = token.clone();
+			async move {
+				token.cancelled().await;
+				tokio::time::sleep(Duration::from_secs(10)).await;
+			}
+		});
+
+		// Act
+		token.cancel();
+		let report = shutdown.drain().await;
+
+		// Assert
+		assert_eq!(report.unfinished(), ["slow"]);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn drains_when_tasks_finish_on_their_own() {
+		// Arrange
+		let shutdown = Shutdown::new();
+		let token = shutdown.token().clone();
+		shutdown.spawn("short", tokio::time::sleep(Duration::from_secs(1)));
+
+		// Act
+		let report = shutdown.drain().await;
+
+		// Assert
+		assert!(report.is_clean());
+		assert!(token.is_cancelled());
+	}
+
+	#[tokio::test]
+	async fn cancelled_completes_on_cancel() {
+		// Arrange
+		let shutdown = Shutdown::new();
+		let cancelled = shutdown.cancelled();
+
+		// Act
+		shutdown.token().cancel();
+
+		// Assert
+		cancelled.await;
+	}
+}
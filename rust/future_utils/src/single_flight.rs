@@ -0,0 +1,238 @@
+use futures::future::{BoxFuture, Shared};
+use futures::FutureExt;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+type Flight<V> = Shared<BoxFuture<'static, V>>;
+
+/// Deduplicates concurrent computations by key.
+///
+/// The first caller for a key starts the computation, callers arriving while it is in flight await
+/// its result instead of starting their own. Errors are shared just like successes, hence `V` is
+/// typically a `Result` with an `Arc`ed error.
+///
+/// The computation is driven by all of its callers, so it keeps running as long as any of them is
+/// waiting, even when the caller which started it is dropped. It is dropped once nobody waits for
+/// it anymore, the next caller then starts over.
+pub struct SingleFlight<K, V> {
+	state: Arc<Mutex<State<K, V>>>,
+}
+
+struct State<K, V> {
+	next_id: u64,
+	flights: HashMap<K, (u64, Flight<V>)>,
+}
+
+impl<K, V> SingleFlight<K, V>
+where
+	K: Eq + Hash + Clone,
+	V: Clone + Send + Sync + 'static,
+{
+	pub fn new() -> SingleFlight<K, V> {
+		SingleFlight {
+			state: Arc::new(Mutex::new(State {
+				next_id: 0,
+				flights: HashMap::new(),
+			})),
+		}
+	}
+
+	/// Awaits the computation in flight for `key`, starting it with `f` if there is none.
+	pub async fn run<F, Fut>(&self, key: K, f: F) -> V
+	where
+		F: FnOnce() -> Fut,
+		Fut: Future<Output = V> + Send + 'static,
+	{
+		let (id, flight) = {
+			let mut state = self.state.lock().expect("lock not poisoned");
+			match state.flights.get(&key) {
+				Some((id, flight)) => (*id, flight.clone()),
+				None => {
+					let id = state.next_id;
+					state.next_id += 1;
+
+					let flight = f().boxed().shared();
+					state.flights.insert(key.clone(), (id, flight.clone()));
+					(id, flight)
+				}
+			}
+		};
+
+		let mut guard = FlightGuard {
+			key,
+			id,
+			flight: Some(flight),
+			state: self.state.clone(),
+		};
+
+		guard
+			.flight
+			.as_mut()
+			.expect("flight only taken when dropped")
+			.await
+	}
+
+	/// Whether a computation is in flight for `key`.
+	pub fn is_in_flight(&self, key: &K) -> bool {
+		let state = self.state.lock().expect("lock not poisoned");
+		state.flights.contains_key(key)
+	}
+}
+
+impl<K, V> Default for SingleFlight<K, V>
+where
+	K: Eq + Hash + Clone,
+	V: Clone + Send + Sync + 'static,
+{
+	fn default() -> Self {
+		SingleFlight::new()
+	}
+}
+
+/// Forgets the flight once it completed or its last caller is dropped.
+struct FlightGuard<K: Eq + Hash, V> {
+	key: K,
+	id: u64,
+	flight: Option<Flight<V>>,
+	state: Arc<Mutex<State<K, V>>>,
+}
+
+impl<K: Eq + Hash, V> Drop for FlightGuard<K, V> {
+	fn drop(&mut self) {
+		drop(self.flight.take());
+
+		let mut state = self.state.lock().expect("lock not poisoned");
+		let Some((id, flight)) = state.flights.get(&self.key) else {
+			return;
+		};
+
+		// a count of none means the flight completed, one means only the map still holds it
+		let is_done = flight.strong_count().is_none_or(|count| count == 1);
+		if *id == self.id && is_done {
+			state.flights.remove(&self.key);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::atomic::{AtomicU32, Ordering};
+	use std::time::Duration;
+
+	fn counted(
+		count: &Arc<AtomicU32>,
+		value: Result<u32, Arc<String>>,
+	) -> impl Future<Output = Result<u32, Arc<String>>> + Send + 'static {
+		let count = count.clone();
+		async move {
+			count.fetch_add(1, Ordering::SeqCst);
+			tokio::time::sleep(Duration::from_secs(1)).await;
+			value
+		}
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn coalesces_concurrent_calls() {
+		// Arrange
+		let single_flight = SingleFlight::new();
+		let count = Arc::new(AtomicU32::new(0));
+
+		// Act
+		let (first, second) = tokio::join!(
+			single_flight.run("token", || counted(&count, Ok(1))),
+			single_flight.run("token", || counted(&count, Ok(2))),
+		);
+
+		// Assert
+		assert_eq!(count.load(Ordering::SeqCst), 1);
+		assert_eq!(first, Ok(1));
+		assert_eq!(second, Ok(1));
+		assert!(!single_flight.is_in_flight(&"token"));
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn shares_errors() {
+		// Arrange
+		let single_flight = SingleFlight::new();
+		let count = Arc::new(AtomicU32::new(0));
+		let error = Arc::new(String::from("rate limited"));
+
+		// Act
+		let (first, second) = tokio::join!(
+			single_flight.run("institutions", || counted(&count, Err(error.clone()))),
+			single_flight.run("institutions", || counted(&count, Ok(2))),
+		);
+
+		// Assert
+		assert_eq!(count.load(Ordering::SeqCst), 1);
+		assert_eq!(first, Err(error.clone()));
+		assert_eq!(second, Err(error));
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn reruns_after_completion() {
+		// Arrange
+		let single_flight = SingleFlight::new();
+		let count = Arc::new(AtomicU32::new(0));
+
+		// Act
+		let first = single_flight.run("token", || counted(&count, Ok(1))).await;
+		let second = single_flight.run("token", || counted(&count, Ok(2))).await;
+
+		// Assert
+		assert_eq!(count.load(Ordering::SeqCst), 2);
+		assert_eq!(first, Ok(1));
+		assert_eq!(second, Ok(2));
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn survives_dropped_leader() {
+		// Arrange
+		let single_flight = Arc::new(SingleFlight::new());
+		let count = Arc::new(AtomicU32::new(0));
+		let leader = tokio::spawn({
+			let single_flight = single_flight.clone();
+			let count = count.clone();
+			async move { single_flight.run("token", || counted(&count, Ok(1))).await }
+		});
+		tokio::time::sleep(Duration::from_millis(100)).await;
+		let follower = tokio::spawn({
+			let single_flight = single_flight.clone();
+			let count = count.clone();
+			async move { single_flight.run("token", || counted(&count, Ok(2))).await }
+		});
+		tokio::time::sleep(Duration::from_millis(100)).await;
+
+		// Act
+		leader.abort();
+		let res = follower.await.unwrap();
+
+		// Assert
+		assert_eq!(count.load(Ordering::SeqCst), 1);
+		assert_eq!(res, Ok(1));
+		assert!(!single_flight.is_in_flight(&"token"));
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn forgets_abandoned_flight() {
+		// Arrange
+		let single_flight = SingleFlight::new();
+		let count = Arc::new(AtomicU32::new(0));
+		let abandoned = tokio::time::timeout(
+			Duration::from_millis(100),
+			single_flight.run("token", || counted(&count, Ok(1))),
+		)
+		.await;
+
+		// Act
+		let res = single_flight.run("token", || counted(&count, Ok(2))).await;
+
+		// Assert
+		assert!(abandoned.is_err());
+		assert_eq!(count.load(Ordering::SeqCst), 2);
+		assert_eq!(res, Ok(2));
+	}
+}
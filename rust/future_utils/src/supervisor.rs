@@ -0,0 +1,409 @@
+use crate::BackoffPolicy;
+use error::ErrorExt;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+type ChildFuture = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+type ChildFactory = Box<dyn FnMut(CancellationToken) -> ChildFuture + Send>;
+type ErrorHandler = Arc<dyn Fn(&str, Duration, &str) + Send + Sync>;
+type Statuses = Arc<Mutex<BTreeMap<String, ChildStatus>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartStrategy {
+	/// Restarts only the failed child.
+	OneForOne,
+	/// Restarts every child when one of them fails, for children which depend on each other.
+	OneForAll,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChildStatus {
+	Running,
+	/// Waiting `delay` before being restarted. The error is only present for the child which
+	/// failed, not for the siblings restarted along with it.
+	Restarting {
+		delay: Duration,
+		error: Option<String>,
+	},
+	/// Completed successfully, hence not restarted.
+	Finished,
+	/// Failed once the backoff policy ran out of attempts.
+	Failed {
+		error: String,
+	},
+	/// Cancelled by shutting down the supervisor.
+	Stopped,
+}
+
+struct Child {
+	name: String,
+	factory: ChildFactory,
+}
+
+/// Owns named long-running child tasks, restarting them with backoff when they fail.
+///
+/// Children are constructed by a factory which receives a [`CancellationToken`], cancelled when the
+/// child is about to be stopped, either due to the supervisor shutting down or a sibling failing
+/// under [`RestartStrategy::OneForAll`]. The child is dropped at its next await point after that.
+pub struct Supervisor {
+	strategy: RestartStrategy,
+	policy: BackoffPolicy,
+	cancellation_token: CancellationToken,
+	on_error: ErrorHandler,
+	children: Vec<Child>,
+}
+
+impl Supervisor {
+	pub fn new(strategy: RestartStrategy) -> Supervisor {
+		Supervisor {
+			strategy,
+			policy: BackoffPolicy::default(),
+			cancellation_token: CancellationToken::new(),
+			on_error: Arc::new(|_, _, _| {}),
+			children: Vec::new(),
+		}
+	}
+
+	pub fn with_policy(mut self, policy: BackoffPolicy) -> Supervisor {
+		self.policy = policy;
+		self
+	}
+
+	/// Shuts the supervisor down once `cancellation_token` is cancelled.
+	pub fn with_cancellation_token(mut self, cancellation_token: CancellationToken) -> Supervisor {
+		self.cancellation_token = cancellation_token;
+		self
+	}
+
+	/// Calls `on_error` with the name of the failed child, the delay before restarting it and its
+	/// error chain, every time a child is about to be restarted.
+	pub fn with_on_error(
+		mut self,
+		on_error: impl Fn(&str, Duration, &str) + Send + Sync + 'static,
+	) -> Supervisor {
+		self.on_error = Arc::new(on_error);
+		self
+	}
+
+	/// # Panics
+	/// If a child named `name` has already been added.
+	pub fn with_child<Fut, E>(
+		mut self,
+		name: impl Into<String>,
+		mut factory: impl FnMut(CancellationToken) -> Fut + Send + 'static,
+	) -> Supervisor
+	where
+		Fut: Future<Output = Result<(), E>> + Send + 'static,
+		E: Error + 'static,
+	{
+		let name = name.into();
+		assert!(
+			self.children.iter().all(|child| child.name != name),
+			"child {name} should only be added once"
+		);
+
+		let factory: ChildFactory = Box::new(move |cancellation_token| {
+			let fut = factory(cancellation_token);
+			Box::pin(async move { fut.await.map_err(|err| err.to_pretty_string()) })
+		});
+
+		self.children.push(Child { name, factory });
+		self
+	}
+
+	pub fn start(self) -> SupervisorHandle {
+		let statuses = self
+			.children
+			.iter()
+			.map(|child| (child.name.clone(), ChildStatus::Running))
+			.collect();
+		let statuses = Arc::new(Mutex::new(statuses));
+
+		let groups = match self.strategy {
+			RestartStrategy::OneForOne => self.children.into_iter().map(|c| vec![c]).collect(),
+			RestartStrategy::OneForAll => vec![self.children],
+		};
+
+		let mut groups_tasks = JoinSet::new();
+		for group in groups {
+			groups_tasks.spawn(run_group(
+				group,
+				self.policy.clone(),
+				self.cancellation_token.clone(),
+				statuses.clone(),
+				self.on_error.clone(),
+			));
+		}
+
+		let join_handle = tokio::spawn(async move {
+			while let Some(res) = groups_tasks.join_next().await {
+				res.expect("supervision of child group should not panic");
+			}
+		});
+
+		SupervisorHandle {
+			statuses,
+			cancellation_token: self.cancellation_token,
+			join_handle,
+		}
+	}
+}
+
+pub struct SupervisorHandle {
+	statuses: Statuses,
+	cancellation_token: CancellationToken,
+	join_handle: JoinHandle<()>,
+}
+
+impl SupervisorHandle {
+	pub fn statuses(&self) -> BTreeMap<String, ChildStatus> {
+		self.statuses.lock().expect("lock not poisoned").clone()
+	}
+
+	pub fn status(&self, name: &str) -> Option<ChildStatus> {
+		self.statuses
+			.lock()
+			.expect("lock not poisoned")
+			.get(name)
+			.cloned()
+	}
+
+	pub fn cancellation_token(&self) -> &CancellationToken {
+		&self.cancellation_token
+	}
+
+	/// Waits until every child has either finished or failed for good.
+	pub async fn join(self) {
+		self.join_handle
+			.await
+			.expect("supervisor task should not panic")
+	}
+
+	/// Stops every child and waits for them to be dropped.
+	pub async fn shutdown(self) {
+		self.cancellation_token.cancel();
+		self.join().await
+	}
+}
+
+async fn run_group(
+	mut children: Vec<Child>,
+	policy: BackoffPolicy,
+	cancellation_token: CancellationToken,
+	statuses: Statuses,
+	on_error: ErrorHandler,
+) {
+	let set_status = |child: &Child, status: ChildStatus| {
+		let mut statuses = statuses.lock().expect("lock not poisoned");
+		statuses.insert(child.name.clone(), status);
+	};
+
+	let mut backoff = policy.backoff();
+	let mut running = (0..children.len()).collect::<Vec<_>>();
+
+	loop {
+		let group_token = cancellation_token.child_token();
+		let start = Instant::now();
+
+		let mut tasks = JoinSet::new();
+		let mut indices = HashMap::new();
+		for &i in &running {
+			let child = &mut children[i];
+			set_status(child, ChildStatus::Running);
+
+			let fut = (child.factory)(group_token.clone());
+			let token = group_token.clone();
+			let handle = tasks.spawn(async move { token.run_until_cancelled(fut).await });
+			indices.insert(handle.id(), i);
+		}
+
+		let mut failure = None;
+		while let Some(res) = tasks.join_next_with_id().await {
+			let (i, res) = match res {
+				Ok((id, res)) => (indices[&id], res),
+				Err(err) => (indices[&err.id()], Some(Err(err.to_string()))),
+			};
+
+			match res {
+				None => {}
+				Some(Ok(())) => {
+					set_status(&children[i], ChildStatus::Finished);
+					running.retain(|&j| j != i);
+				}
+				Some(Err(error)) => {
+					failure.get_or_insert((i, error));
+					group_token.cancel();
+				}
+			}
+		}
+
+		if cancellation_token.is_cancelled() {
+			for &i in &running {
+				set_status(&children[i], ChildStatus::Stopped);
+			}
+			return;
+		}
+
+		let Some((failed, error)) = failure else {
+			return;
+		};
+
+		let Some(delay) = backoff.next(start.elapsed()) else {
+			for &i in &running {
+				let status = match i == failed {
+					true => ChildStatus::Failed {
+						error: error.clone(),
+					},
+					false => ChildStatus::Stopped,
+				};
+				set_status(&children[i], status);
+			}
+			return;
+		};
+
+		on_error(&children[failed].name, delay, &error);
+
+		for &i in &running {
+			let status = ChildStatus::Restarting {
+				delay,
+				error: (i == failed).then(|| error.clone()),
+			};
+			set_status(&children[i], status);
+		}
+
+		tokio::select! {
+			_ = tokio::time::sleep(delay) => {},
+			_ = cancellation_token.cancelled() => {
+				for &i in &running {
+					set_status(&children[i], ChildStatus::Stopped);
+				}
+				return;
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::atomic::{AtomicU32, Ordering};
+
+	#[derive(Debug, thiserror::Error)]
+	#[error("child failed")]
+	struct ChildError;
+
+	fn counting_child(
+		count: &Arc<AtomicU32>,
+		fail: bool,
+	) -> impl FnMut(CancellationToken) -> Pin<Box<dyn Future<Output = Result<(), ChildError>> + Send>>
+	{
+		let count = count.clone();
+		move |_| {
+			let count = count.clone();
+			Box::pin(async move {
+				count.fetch_add(1, Ordering::SeqCst);
+				tokio::time::sleep(Duration::from_secs(1)).await;
+				match fail {
+					true => Err(ChildError),
+					false => std::future::pending().await,
+				}
+			})
+		}
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn one_for_one_restarts_failed_child() {
+		// Arrange
+		let failing = Arc::new(AtomicU32::new(0));
+		let healthy = Arc::new(AtomicU32::new(0));
+		let supervisor = Supervisor::new(RestartStrategy::OneForOne)
+			.with_policy(BackoffPolicy::constant(Duration::from_secs(1)))
+			.with_child("failing", counting_child(&failing, true))
+			.with_child("healthy", counting_child(&healthy, false));
+
+		// Act
+		let handle = supervisor.start();
+		tokio::time::sleep(Duration::from_millis(3500)).await;
+
+		// Assert
+		assert_eq!(failing.load(Ordering::SeqCst), 2);
+		assert_eq!(healthy.load(Ordering::SeqCst), 1);
+		assert_eq!(handle.status("healthy"), Some(ChildStatus::Running));
+		assert_eq!(
+			handle.status("failing"),
+			Some(ChildStatus::Restarting {
+				delay: Duration::from_secs(1),
+				error: Some(String::from("child failed")),
+			})
+		);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn one_for_all_restarts_siblings() {
+		// Arrange
+		let failing = Arc::new(AtomicU32::new(0));
+		let healthy = Arc::new(AtomicU32::new(0));
+		let supervisor = Supervisor::new(RestartStrategy::OneForAll)
+			.with_policy(BackoffPolicy::constant(Duration::from_secs(1)))
+			.with_child("failing", counting_child(&failing, true))
+			.with_child("healthy", counting_child(&healthy, false));
+
+		// Act
+		let _handle = supervisor.start();
+		tokio::time::sleep(Duration::from_millis(4500)).await;
+
+		// Assert
+		assert_eq!(failing.load(Ordering::SeqCst), 3);
+		assert_eq!(healthy.load(Ordering::SeqCst), 3);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn gives_up_after_max_attempts() {
+		// Arrange
+		let failing = Arc::new(AtomicU32::new(0));
+		let supervisor = Supervisor::new(RestartStrategy::OneForOne)
+			.with_policy(BackoffPolicy::constant(Duration::from_secs(1)).with_max_attempts(2))
+			.with_child("failing", counting_child(&failing, true));
+		let handle = supervisor.start();
+		let statuses = handle.statuses.clone();
+
+		// Act
+		handle.join().await;
+
+		// Assert
+		assert_eq!(failing.load(Ordering::SeqCst), 2);
+		assert_eq!(
+			statuses.lock().unwrap().get("failing"),
+			Some(&ChildStatus::Failed {
+				error: String::from("child failed"),
+			})
+		);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn shutdown_stops_children() {
+		// Arrange
+		let healthy = Arc::new(AtomicU32::new(0));
+		let supervisor = Supervisor::new(RestartStrategy::OneForOne)
+			.with_child("healthy", counting_child(&healthy, false));
+		let handle = supervisor.start();
+		let statuses = handle.statuses.clone();
+		tokio::time::sleep(Duration::from_secs(5)).await;
+
+		// Act
+		handle.shutdown().await;
+
+		// Assert
+		assert_eq!(
+			statuses.lock().unwrap().get("healthy"),
+			Some(&ChildStatus::Stopped)
+		);
+	}
+}
@@ -4,8 +4,7 @@ mod user;
 
 use crate::api::graphql::institution::InstitutionQuery;
 use crate::api::graphql::user::{UserMutation, UserQuery};
-use crate::nordigen_token_client::NordigenTokenClient;
-use crate::token_manager::{TokenClient, TokenManager};
+use crate::token_manager::TokenManager;
 use async_graphql::{http::GraphiQLSource, EmptySubscription, MergedObject, Schema};
 use async_graphql_axum::GraphQL;
 use axum::response::{Html, IntoResponse};
@@ -26,7 +25,7 @@ async fn graphiql() -> impl IntoResponse {
 pub fn build_router(
 	mongo_client: Client,
 	nordigen_client: http_api::nordigen::Client,
-	token_manager: TokenManager<NordigenTokenClient>,
+	token_manager: TokenManager,
 ) -> Router<()> {
 	let schema = Schema::build(
 		Query(UserQuery, InstitutionQuery),
@@ -1,9 +1,10 @@
 mod graphql;
 
-use crate::nordigen_token_client::NordigenTokenClient;
-use crate::token_manager::{TokenClient, TokenManager};
+use crate::token_manager::TokenManager;
 use axum::Router;
+use future_utils::Shutdown;
 use mongodb::Client;
+use std::future::IntoFuture;
 use tower_http::trace::{DefaultOnRequest, DefaultOnResponse, TraceLayer};
 use tracing::Level;
 
@@ -13,7 +14,7 @@ impl Api {
 	pub async fn run(
 		mongo_client: Client,
 		nordigen_client: http_api::nordigen::Client,
-		token_manager: TokenManager<NordigenTokenClient>,
+		token_manager: TokenManager,
 	) {
 		let root_router = Router::new()
 			.nest(
@@ -26,6 +27,18 @@ impl Api {
 					.on_response(DefaultOnResponse::new().level(Level::INFO)),
 			);
 		let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
-		axum::serve(listener, root_router).await.unwrap();
+
+		let shutdown = Shutdown::new().cancel_on_signals();
+		let server =
+			axum::serve(listener, root_router).with_graceful_shutdown(shutdown.cancelled());
+		let server = shutdown.spawn("http server", server.into_future());
+
+		let report = shutdown.drain().await;
+		if !report.is_clean() {
+			tracing::warn!("{report}");
+			return;
+		}
+
+		server.await.unwrap().unwrap();
 	}
 }
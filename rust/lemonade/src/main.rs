@@ -5,9 +5,12 @@ use crate::nordigen_token_client::NordigenTokenClient;
 use crate::token_manager::TokenManager;
 use ::config::FromConfig;
 use error::extensions::result::ResultExt;
+use logger::LoggerLayer;
 use mongodb::Client;
 use std::time::Duration;
 use tracing::info;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 use uuid::{NoContext, Timestamp, Uuid};
 
 mod api;
@@ -18,7 +21,9 @@ mod nordigen_token_client;
 mod token_manager;
 
 fn main() -> anyhow::Result<()> {
-	tracing_subscriber::fmt().compact().init();
+	tracing_subscriber::registry()
+		.with(LoggerLayer::new())
+		.init();
 	info!("initialized tracing subscriber");
 
 	let config = Config::parse().must();
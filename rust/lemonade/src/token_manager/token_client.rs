@@ -1,9 +1,16 @@
 use super::{AccessAndRefreshToken, Secret, Token};
 use std::error::Error;
+use std::future::Future;
 
 pub trait TokenClient {
 	type AcquireError: 'static + Error + Send + Sync;
 	type RefreshError: 'static + Error + Send + Sync;
-	async fn acquire(&self, secret: &Secret) -> Result<AccessAndRefreshToken, Self::AcquireError>;
-	async fn refresh(&self, refresh: &str) -> Result<Token, Self::RefreshError>;
+	fn acquire(
+		&self,
+		secret: &Secret,
+	) -> impl Future<Output = Result<AccessAndRefreshToken, Self::AcquireError>> + Send;
+	fn refresh(
+		&self,
+		refresh: &str,
+	) -> impl Future<Output = Result<Token, Self::RefreshError>> + Send;
 }
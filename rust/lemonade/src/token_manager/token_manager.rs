@@ -1,84 +1,98 @@
 use crate::token_manager::{AccessAndRefreshToken, Secret, Token, TokenClient};
-use chrono::Utc;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use ::error::ErrorExt;
+use chrono::{TimeDelta, Utc};
+use std::sync::{Arc, Mutex};
+use sync_utils::{Refreshed, RefreshingCell, RefreshingCellOptions};
+use thiserror::Error;
+use tracing::warn;
 
-pub struct TokenManager<C> {
-	secret: Secret,
-	access: RwLock<Option<Token>>,
-	refresh: RwLock<Option<Token>>,
-	token_client: C,
+pub struct TokenManager {
+	access: RefreshingCell<Arc<str>>,
 }
 
-impl<C: TokenClient> TokenManager<C> {
-	pub fn new(
+impl TokenManager {
+	pub fn new<C: TokenClient + Send + Sync + 'static>(
 		secret_id: impl Into<Box<str>>,
 		secret_key: impl Into<Box<str>>,
 		token_client: C,
-	) -> TokenManager<C> {
-		TokenManager {
-			secret: Secret {
-				id: secret_id.into(),
-				key: secret_key.into(),
-			},
-			access: RwLock::new(None),
-			refresh: RwLock::new(None),
-			token_client,
-		}
-	}
-
-	#[allow(unused)]
-	pub async fn access_token(&self) -> anyhow::Result<Arc<str>> {
-		let token = {
-			self.access
-				.read()
-				.await
-				.as_ref()
-				.map(|token| (token.value.clone(), token.expires_at))
-		};
+	) -> TokenManager {
+		let secret = Arc::new(Secret {
+			id: secret_id.into(),
+			key: secret_key.into(),
+		});
+		let token_client = Arc::new(token_client);
+		let refresh = Arc::new(Mutex::new(None));
 
-		match token {
-			None => self.refresh_access_token().await,
-			Some((_, expires_at)) if Utc::now() < expires_at => self.refresh_access_token().await,
-			Some((token, _)) => Ok(token),
-		}
-	}
+		let options = RefreshingCellOptions::default().with_on_error(|delay, err| {
+			warn!(
+				"failed to fetch access token, retrying in {delay:?}\nerror cause: {}",
+				err.to_pretty_string()
+			)
+		});
 
-	async fn refresh_access_token(&self) -> anyhow::Result<Arc<str>> {
-		let token = {
-			self.refresh
-				.read()
-				.await
-				.as_ref()
-				.map(|token| (token.value.clone(), token.expires_at))
-		};
+		let access = RefreshingCell::with_options(options, move || {
+			let secret = secret.clone();
+			let token_client = token_client.clone();
+			let refresh = refresh.clone();
 
-		let token = match token {
-			None => return self.refresh_refresh_token().await,
-			Some((_, expires_at)) if Utc::now() < expires_at => {
-				return self.refresh_refresh_token().await
-			}
-			Some((token, _)) => token,
-		};
+			async move { fetch_access_token(&secret, &*token_client, &refresh).await }
+		});
 
-		let access = dbg!(self.token_client.refresh(token.as_ref()).await?);
+		TokenManager { access }
+	}
 
-		let res = access.value.clone();
+	pub async fn access_token(&self) -> Arc<str> {
+		self.access.get().await
+	}
+}
 
-		(*self.access.write().await) = Some(access);
+/// Refreshes the access token while the refresh token is valid, acquires a new pair otherwise.
+async fn fetch_access_token<C: TokenClient>(
+	secret: &Secret,
+	token_client: &C,
+	refresh: &Mutex<Option<Token>>,
+) -> Result<Refreshed<Arc<str>>, FetchAccessTokenError<C::AcquireError, C::RefreshError>> {
+	let valid_refresh = {
+		let refresh = refresh.lock().expect("lock not poisoned");
+		refresh
+			.as_ref()
+			.filter(|refresh| Utc::now() + TimeDelta::seconds(1) < refresh.expires_at)
+			.map(|refresh| refresh.value.clone())
+	};
 
-		Ok(res)
-	}
+	let access = match valid_refresh {
+		Some(valid_refresh) => token_client
+			.refresh(&valid_refresh)
+			.await
+			.map_err(FetchAccessTokenError::Refresh)?,
+		None => {
+			let AccessAndRefreshToken {
+				access,
+				refresh: new_refresh,
+			} = token_client
+				.acquire(secret)
+				.await
+				.map_err(FetchAccessTokenError::Acquire)?;
 
-	async fn refresh_refresh_token(&self) -> anyhow::Result<Arc<str>> {
-		let AccessAndRefreshToken { access, refresh } =
-			self.token_client.acquire(&self.secret).await?;
+			*refresh.lock().expect("lock not poisoned") = Some(new_refresh);
+			access
+		}
+	};
 
-		let res = access.value.clone();
+	let expires_in = (access.expires_at - Utc::now())
+		.to_std()
+		.unwrap_or_default();
 
-		(*self.access.write().await) = Some(access);
-		(*self.refresh.write().await) = Some(refresh);
+	Ok(Refreshed {
+		value: access.value,
+		expires_in,
+	})
+}
 
-		Ok(res)
-	}
+#[derive(Debug, Error)]
+enum FetchAccessTokenError<A, R> {
+	#[error("failed to acquire access and refresh token")]
+	Acquire(#[source] A),
+	#[error("failed to refresh access token")]
+	Refresh(#[source] R),
 }
@@ -1,5 +1,5 @@
 use crate::domain::lemonade::ports::random_provider::RandomProvider;
-use crate::macros::new_str;
+use constrained_str::ConstrainedStr;
 use error::InfallibleResultExt;
 use std::any::type_name;
 use std::error::Error;
@@ -11,7 +11,9 @@ pub trait Filter {
 	fn matches(&self, value: &Self::Resource) -> bool;
 }
 
-new_str!(pub ActorId);
+#[derive(Debug, PartialEq, Eq, Hash, ConstrainedStr)]
+#[repr(transparent)]
+pub struct ActorId(str);
 
 #[derive(Debug, Clone)]
 pub struct ApiKeyResource {
@@ -23,16 +25,15 @@ impl ActorId {
 	pub fn generate_random<RP: RandomProvider>(random_provider: &RP) -> Box<ActorId> {
 		let random_value = random_provider.random();
 		let random_value = format!("{random_value:032X}");
-		let random_value = random_value
-			.as_str()
-			.parse::<&ActorId>()
-			.unwrap_infallible();
+		let random_value = ActorId::new(&random_value).unwrap_infallible();
 		let random_value = Box::from(random_value);
 		random_value
 	}
 }
 
-new_str!(pub ApiKey);
+#[derive(Debug, PartialEq, Eq, Hash, ConstrainedStr)]
+#[repr(transparent)]
+pub struct ApiKey(str);
 
 pub struct CreateUserOptions<'l> {
 	pub name: &'l str,
@@ -4,8 +4,8 @@ use crate::domain::lemonade::ports::lemonade_service::errors::{
 use crate::domain::lemonade::ports::store;
 use crate::domain::lemonade::ports::store::errors::StoreFindApiKeyError;
 use axum::http::StatusCode;
-use axum::response::{IntoResponse, Response};
-use std::error::Error;
+use axum_extra::extract::JsonDeserializerRejection;
+use error::{ErrorResponse, HttpError, ProblemDetails};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -16,87 +16,83 @@ pub enum StartError {
 	Serve(#[source] std::io::Error),
 }
 
-pub struct AppError {
-	response: Response,
-}
+pub type AppError = ErrorResponse;
 
-impl IntoResponse for AppError {
-	fn into_response(self) -> Response {
-		self.response
-	}
-}
+#[derive(Debug, Error)]
+#[error("could not deserialize request body")]
+pub struct InvalidRequestBody(#[from] JsonDeserializerRejection);
 
-impl<E: Error + IntoResponse> From<E> for AppError {
-	fn from(error: E) -> Self {
-		AppError {
-			response: error.into_response(),
-		}
+impl HttpError for InvalidRequestBody {
+	fn problem_details(&self) -> ProblemDetails {
+		ProblemDetails::new(self.0.status()).with_detail(self.0.body_text())
 	}
 }
 
-impl IntoResponse for store::errors::StoreFindOneUserError {
-	fn into_response(self) -> Response {
+impl HttpError for store::errors::StoreFindOneUserError {
+	fn problem_details(&self) -> ProblemDetails {
 		match self {
-			store::errors::StoreFindOneUserError::NotFound => StatusCode::NOT_FOUND.into_response(),
+			store::errors::StoreFindOneUserError::NotFound => {
+				ProblemDetails::new(StatusCode::NOT_FOUND)
+			}
 			store::errors::StoreFindOneUserError::MoreThanOneFound(_) | Self::Unknown(_) => {
-				StatusCode::INTERNAL_SERVER_ERROR.into_response()
+				ProblemDetails::new(StatusCode::INTERNAL_SERVER_ERROR)
 			}
 		}
 	}
 }
 
-impl IntoResponse for store::errors::StoreCreateUserError {
-	fn into_response(self) -> Response {
+impl HttpError for store::errors::StoreCreateUserError {
+	fn problem_details(&self) -> ProblemDetails {
 		match self {
 			store::errors::StoreCreateUserError::Unknown(_) => {
-				StatusCode::INTERNAL_SERVER_ERROR.into_response()
+				ProblemDetails::new(StatusCode::INTERNAL_SERVER_ERROR)
 			}
 		}
 	}
 }
 
-impl IntoResponse for StoreFindApiKeyError {
-	fn into_response(self) -> Response {
+impl HttpError for StoreFindApiKeyError {
+	fn problem_details(&self) -> ProblemDetails {
 		match self {
-			StoreFindApiKeyError::NotFound => StatusCode::NOT_FOUND.into_response(),
+			StoreFindApiKeyError::NotFound => ProblemDetails::new(StatusCode::NOT_FOUND),
 			StoreFindApiKeyError::Unknown(_) | StoreFindApiKeyError::MoreThanOneFound(_) => {
-				StatusCode::INTERNAL_SERVER_ERROR.into_response()
+				ProblemDetails::new(StatusCode::INTERNAL_SERVER_ERROR)
 			}
 		}
 	}
 }
 
-impl IntoResponse for AuthenticateWithApiKeyError {
-	fn into_response(self) -> Response {
+impl HttpError for AuthenticateWithApiKeyError {
+	fn problem_details(&self) -> ProblemDetails {
 		match self {
-			AuthenticateWithApiKeyError::Store { source, .. } => source.into_response(),
+			AuthenticateWithApiKeyError::Store { source, .. } => source.problem_details(),
 		}
 	}
 }
 
-impl IntoResponse for AuthenticateWithApiPasswordError {
-	fn into_response(self) -> Response {
+impl HttpError for AuthenticateWithApiPasswordError {
+	fn problem_details(&self) -> ProblemDetails {
 		match self {
-			AuthenticateWithApiPasswordError::Store { source, .. } => source.into_response(),
+			AuthenticateWithApiPasswordError::Store { source, .. } => source.problem_details(),
 			AuthenticateWithApiPasswordError::BadPassword => {
-				StatusCode::UNAUTHORIZED.into_response()
+				ProblemDetails::new(StatusCode::UNAUTHORIZED)
 			}
 		}
 	}
 }
 
-impl IntoResponse for CreateUserError {
-	fn into_response(self) -> Response {
+impl HttpError for CreateUserError {
+	fn problem_details(&self) -> ProblemDetails {
 		match self {
-			CreateUserError::Store { source, .. } => source.into_response(),
+			CreateUserError::Store { source, .. } => source.problem_details(),
 		}
 	}
 }
 
-impl IntoResponse for FindUserError {
-	fn into_response(self) -> Response {
+impl HttpError for FindUserError {
+	fn problem_details(&self) -> ProblemDetails {
 		match self {
-			FindUserError::Store { source, .. } => source.into_response(),
+			FindUserError::Store { source, .. } => source.problem_details(),
 		}
 	}
 }
@@ -80,7 +80,7 @@ trait PartsExt: Sync {
 	) -> impl Future<Output = Result<Arc<ActorId>, AuthenticateByApiKeyError>> + Send {
 		async {
 			let api_key = self.extract_bearer_token()?;
-			let api_key = api_key.parse::<&ApiKey>().unwrap_infallible();
+			let api_key = ApiKey::new(api_key).unwrap_infallible();
 			let actor_id = lemonade_service.authenticate_with_api_key(api_key).await?;
 
 			Ok(actor_id)
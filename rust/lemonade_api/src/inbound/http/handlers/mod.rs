@@ -25,7 +25,7 @@ mod users {
 	mod post {
 		use crate::domain::lemonade::models::CreateUserOptions;
 		use crate::domain::lemonade::ports::lemonade_service::LemonadeService;
-		use crate::inbound::http::error::AppError;
+		use crate::inbound::http::error::{AppError, InvalidRequestBody};
 		use axum::extract::{FromRequest, FromRequestParts, State};
 		use axum::response::IntoResponse;
 		use axum::Json;
@@ -83,7 +83,9 @@ mod users {
 			State(lemonade_service): State<Arc<LS>>,
 			request_body: JsonDeserializer<dto::Request<'_>>,
 		) -> Result<Json<dto::Response>, AppError> {
-			let request_body = request_body.deserialize()?;
+			let request_body = request_body
+				.deserialize()
+				.map_err(InvalidRequestBody::from)?;
 			let create_user_options = CreateUserOptions::from(&request_body);
 
 			let response_body = lemonade_service
@@ -119,7 +121,7 @@ mod users {
 
 			impl From<UserIdPathParam> for Box<ActorId> {
 				fn from(value: UserIdPathParam) -> Self {
-					value.user_id.parse::<&ActorId>().unwrap_infallible().into()
+					ActorId::new(&value.user_id).unwrap_infallible().into()
 				}
 			}
 		}
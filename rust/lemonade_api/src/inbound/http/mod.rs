@@ -2,6 +2,8 @@ use crate::domain::lemonade::service::Service;
 use crate::inbound::http::app_state::AppState;
 use crate::outbound::{InMemoryRepository, Random};
 pub use error::StartError;
+use future_utils::Shutdown;
+use std::future::IntoFuture;
 
 pub async fn start() -> Result<(), StartError> {
 	let random_provider = Random;
@@ -16,11 +18,20 @@ pub async fn start() -> Result<(), StartError> {
 		.await
 		.map_err(StartError::Bind)?;
 
-	axum::serve(listener, app)
-		.await
-		.map_err(StartError::Serve)?;
+	let shutdown = Shutdown::new().cancel_on_signals();
+	let server = axum::serve(listener, app).with_graceful_shutdown(shutdown.cancelled());
+	let server = shutdown.spawn("http server", server.into_future());
+
+	let report = shutdown.drain().await;
+	if !report.is_clean() {
+		tracing::warn!("{report}");
+		return Ok(());
+	}
 
-	Ok(())
+	server
+		.await
+		.expect("http server should not panic")
+		.map_err(StartError::Serve)
 }
 
 mod app_state;
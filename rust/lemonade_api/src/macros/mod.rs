@@ -1,4 +1 @@
 mod into_http_error;
-mod new_str;
-
-pub(crate) use new_str::new_str;
@@ -1,4 +1,7 @@
 use error::ErrorExt;
+use logger::{Filter, Level, LoggerLayer};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 
 mod domain;
 mod inbound;
@@ -7,8 +10,8 @@ mod outbound;
 
 #[tokio::main]
 async fn main() {
-	tracing_subscriber::FmtSubscriber::builder()
-		.with_max_level(tracing::Level::TRACE)
+	tracing_subscriber::registry()
+		.with(LoggerLayer::new().with_log_filter(Filter::new(Level::Trace)))
 		.init();
 
 	tracing::info!("starting server");
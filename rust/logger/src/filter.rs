@@ -0,0 +1,198 @@
+use crate::Level;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use thiserror::Error;
+
+/// The minimum level per logger name, e.g. `lemonade.db=debug,info`.
+///
+/// A directive applies to the logger of its name and all of its children, the most specific one
+/// wins. A bare level sets the default for loggers no directive applies to, which is `info` unless
+/// given. `off` disables logging.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+	default: Option<Level>,
+	directives: Vec<(String, Option<Level>)>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ParseFilterError {
+	#[error("unknown level `{0}`, expected one of trace, debug, info, warn, error or off")]
+	UnknownLevel(String),
+	#[error("empty logger name in directive `{0}`")]
+	EmptyName(String),
+}
+
+impl Filter {
+	pub fn new(default: Level) -> Filter {
+		Filter {
+			default: Some(default),
+			directives: Vec::new(),
+		}
+	}
+
+	/// Sets the minimum level of the logger `name` and its children, `None` disabling them.
+	pub fn with_directive(mut self, name: &str, level: Option<Level>) -> Filter {
+		self.directives.retain(|(other, _)| other != name);
+		self.directives.push((name.to_string(), level));
+		self
+	}
+
+	/// Parses the `LOG` env variable, falling back to the default filter if it is unset or invalid.
+	pub fn from_env() -> Filter {
+		let Ok(directives) = std::env::var("LOG") else {
+			return Filter::default();
+		};
+
+		directives.parse().unwrap_or_else(|err| {
+			eprintln!("ignoring invalid LOG env variable: {err}");
+			Filter::default()
+		})
+	}
+
+	/// The filter from the `LOG` env variable, read once.
+	pub fn global() -> &'static Filter {
+		global_filter()
+	}
+
+	/// [`Filter::global`], shared rather than copied by every logger using it.
+	pub(crate) fn shared_global() -> Arc<Filter> {
+		global_filter().clone()
+	}
+
+	/// The minimum level of the logger `name`, `None` if it is disabled.
+	pub fn min_level(&self, name: &str) -> Option<Level> {
+		self.directives
+			.iter()
+			.filter(|(prefix, _)| {
+				name.strip_prefix(prefix.as_str())
+					.is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+			})
+			.max_by_key(|(prefix, _)| prefix.len())
+			.map_or(self.default, |(_, level)| *level)
+	}
+
+	/// The lowest level any logger is enabled at, `None` if all of them are disabled.
+	pub fn lowest_level(&self) -> Option<Level> {
+		self.directives
+			.iter()
+			.filter_map(|(_, level)| *level)
+			.chain(self.default)
+			.min()
+	}
+}
+
+impl Default for Filter {
+	fn default() -> Self {
+		Filter::new(Level::Info)
+	}
+}
+
+impl FromStr for Filter {
+	type Err = ParseFilterError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut filter = Filter::default();
+
+		for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+			match directive.split_once('=') {
+				Some((name, level)) => {
+					let name = name.trim();
+					if name.is_empty() {
+						return Err(ParseFilterError::EmptyName(directive.to_string()));
+					}
+					filter = filter.with_directive(name, parse_level(level.trim())?);
+				}
+				None => filter.default = parse_level(directive)?,
+			}
+		}
+
+		Ok(filter)
+	}
+}
+
+fn global_filter() -> &'static Arc<Filter> {
+	static GLOBAL: OnceLock<Arc<Filter>> = OnceLock::new();
+	GLOBAL.get_or_init(|| Arc::new(Filter::from_env()))
+}
+
+fn parse_level(level: &str) -> Result<Option<Level>, ParseFilterError> {
+	match level.to_ascii_lowercase().as_str() {
+		"trace" => Ok(Some(Level::Trace)),
+		"debug" => Ok(Some(Level::Debug)),
+		"info" => Ok(Some(Level::Info)),
+		"warn" => Ok(Some(Level::Warn)),
+		"error" => Ok(Some(Level::Error)),
+		"off" => Ok(None),
+		_ => Err(ParseFilterError::UnknownLevel(level.to_string())),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn most_specific_directive_wins() {
+		// Arrange
+		let filter = "lemonade=warn, lemonade.db=debug,error"
+			.parse::<Filter>()
+			.unwrap();
+
+		// Act
+		let levels = [
+			filter.min_level("lemonade"),
+			filter.min_level("lemonade.db.pool"),
+			filter.min_level("lemonade.dbx"),
+			filter.min_level("waypointer"),
+		];
+
+		// Assert
+		assert_eq!(
+			levels,
+			[
+				Some(Level::Warn),
+				Some(Level::Debug),
+				Some(Level::Warn),
+				Some(Level::Error)
+			]
+		);
+	}
+
+	#[test]
+	fn lowest_level_of_all_directives() {
+		// Arrange
+		let filter = "lemonade=warn, lemonade.db=debug,error"
+			.parse::<Filter>()
+			.unwrap();
+		let disabled = "off,lemonade=off".parse::<Filter>().unwrap();
+
+		// Act
+		let lowest_level = filter.lowest_level();
+		let disabled_lowest_level = disabled.lowest_level();
+
+		// Assert
+		assert_eq!(lowest_level, Some(Level::Debug));
+		assert_eq!(disabled_lowest_level, None);
+	}
+
+	#[test]
+	fn rejects_invalid_directives() {
+		// Arrange
+		let unknown = "lemonade=loud";
+		let nameless = "=debug";
+
+		// Act
+		let unknown = unknown.parse::<Filter>();
+		let nameless = nameless.parse::<Filter>();
+
+		// Assert
+		assert_eq!(
+			unknown,
+			Err(ParseFilterError::UnknownLevel("loud".to_string()))
+		);
+		assert_eq!(
+			nameless,
+			Err(ParseFilterError::EmptyName("=debug".to_string()))
+		);
+	}
+}
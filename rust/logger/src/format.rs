@@ -0,0 +1,137 @@
+use crate::{FieldValue, Record};
+use chrono::SecondsFormat;
+use serde_json::{Map, Value};
+
+/// Turns a record into a single line of output, without the trailing newline.
+pub trait Format: Send + Sync {
+	fn format(&self, record: &Record<'_>) -> String;
+}
+
+/// `"{time} {level} {name} {msg}"` followed by the fields as `key=value`, quoting values which
+/// would otherwise be ambiguous.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextFormat;
+
+/// One JSON object per line, with the fields alongside `time`, `level`, `logger` and `msg`, which
+/// take precedence over fields of the same name.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonFormat;
+
+impl Format for TextFormat {
+	fn format(&self, record: &Record<'_>) -> String {
+		let Record {
+			time,
+			level,
+			name,
+			msg,
+			fields,
+		} = record;
+
+		let mut line = format!("{time} {level} {name} {msg}");
+		for (key, value) in *fields {
+			let value = value.to_string();
+			let is_ambiguous = value.is_empty()
+				|| value.contains(|c: char| c.is_whitespace() || c == '"' || c == '=');
+
+			if is_ambiguous {
+				line.push_str(&format!(" {key}={value:?}"));
+			} else {
+				line.push_str(&format!(" {key}={value}"));
+			}
+		}
+
+		line
+	}
+}
+
+impl Format for JsonFormat {
+	fn format(&self, record: &Record<'_>) -> String {
+		let mut object = Map::new();
+		for (key, value) in record.fields {
+			object.insert(key.to_string(), to_json(value));
+		}
+
+		object.insert(
+			"time".to_string(),
+			Value::from(record.time.to_rfc3339_opts(SecondsFormat::Micros, true)),
+		);
+		object.insert("level".to_string(), Value::from(record.level.to_string()));
+		object.insert("logger".to_string(), Value::from(record.name));
+		object.insert("msg".to_string(), Value::from(record.msg));
+
+		Value::Object(object).to_string()
+	}
+}
+
+fn to_json(value: &FieldValue) -> Value {
+	match value {
+		FieldValue::Str(value) => Value::from(value.as_str()),
+		FieldValue::Int(value) => Value::from(*value),
+		FieldValue::UInt(value) => Value::from(*value),
+		FieldValue::Float(value) => Value::from(*value),
+		FieldValue::Bool(value) => Value::from(*value),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Level;
+	use chrono::{TimeZone, Utc};
+	use serde_json::json;
+
+	fn record<'a>(fields: &'a [(&'static str, FieldValue)]) -> Record<'a> {
+		Record {
+			time: Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap(),
+			level: Level::Info,
+			name: "lemonade.token",
+			msg: "token refreshed",
+			fields,
+		}
+	}
+
+	#[test]
+	fn text_quotes_ambiguous_values() {
+		// Arrange
+		let fields = [
+			("secret_id", FieldValue::from("abc")),
+			("attempt", FieldValue::from(2)),
+			("reason", FieldValue::from("rate limited")),
+		];
+
+		// Act
+		let line = TextFormat.format(&record(&fields));
+
+		// Assert
+		assert_eq!(
+			line,
+			"2024-03-01 12:00:00 UTC INFO lemonade.token token refreshed secret_id=abc attempt=2 reason=\"rate limited\""
+		);
+	}
+
+	#[test]
+	fn json_keeps_field_types() {
+		// Arrange
+		let fields = [
+			("secret_id", FieldValue::from("abc")),
+			("attempt", FieldValue::from(2)),
+			("msg", FieldValue::from(false)),
+		];
+
+		// Act
+		let line = JsonFormat.format(&record(&fields));
+
+		// Assert
+		assert_eq!(
+			serde_json::from_str::<Value>(&line).unwrap(),
+			json!({
+				"time": "2024-03-01T12:00:00.000000Z",
+				"level": "INFO",
+				"logger": "lemonade.token",
+				"msg": "token refreshed",
+				"secret_id": "abc",
+				"attempt": 2,
+			})
+		);
+	}
+}
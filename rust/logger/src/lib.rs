@@ -1,9 +1,26 @@
+mod filter;
+mod format;
+mod output;
+mod record;
+#[cfg(test)]
+mod test_utils;
+#[cfg(feature = "tracing")]
+mod tracing_bridge;
+
+pub use filter::*;
+pub use format::*;
+pub use output::*;
+pub use record::*;
+#[cfg(feature = "tracing")]
+pub use tracing_bridge::*;
+
 use chrono::Utc;
+use std::borrow::Cow;
 use std::error::Error;
-use std::fmt::{Display, Formatter};
+use std::fmt::{Debug, Display, Formatter};
 use std::sync::Arc;
 
-#[derive(Debug, Ord, PartialOrd, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub enum Level {
 	Trace,
 	Debug,
@@ -24,32 +41,145 @@ impl Display for Level {
 	}
 }
 
-#[derive(Debug, Clone)]
+/// Writes [`TextFormat`]ted lines to stdout, filtered by [`Filter::global`], unless configured
+/// otherwise. Children share the filter, format and output of their parent.
+#[derive(Clone)]
 pub struct Logger {
 	pub name: Arc<str>,
+	filter: Arc<Filter>,
+	/// Resolved from the filter once, so that checking whether a level is enabled is cheap.
+	min_level: Option<Level>,
+	format: Arc<dyn Format>,
+	output: Arc<Output>,
+}
+
+/// A log line which is written once dropped, so that fields can be attached before. Nothing is
+/// formatted if its level is disabled, though arguments are still evaluated, unless given lazily
+/// through [`Logger::event_with`] and [`Event::field_with`].
+pub struct Event<'a> {
+	logger: &'a Logger,
+	level: Level,
+	msg: Cow<'a, str>,
+	fields: Vec<(&'static str, FieldValue)>,
 }
 
 impl Logger {
 	pub fn new(name: &str) -> Logger {
+		let filter = Filter::shared_global();
+
 		Logger {
 			name: Arc::from(name),
+			min_level: filter.min_level(name),
+			filter,
+			format: Arc::new(TextFormat),
+			output: Arc::new(Output::Stdout),
 		}
 	}
 
+	pub fn with_filter(mut self, filter: Filter) -> Logger {
+		self.min_level = filter.min_level(&self.name);
+		self.filter = Arc::new(filter);
+		self
+	}
+
+	pub fn with_format(mut self, format: impl Format + 'static) -> Logger {
+		self.format = Arc::new(format);
+		self
+	}
+
+	pub fn with_output(mut self, output: Output) -> Logger {
+		self.output = Arc::new(output);
+		self
+	}
+
 	pub fn child(&self, name: &str) -> Logger {
-		let Logger { name: parent_name } = self;
+		let Logger {
+			name: parent_name,
+			filter,
+			min_level: _,
+			format,
+			output,
+		} = self;
+		let name = format!("{parent_name}.{name}");
+
 		Logger {
-			name: Arc::from(format!("{parent_name}.{name}")),
+			min_level: filter.min_level(&name),
+			name: Arc::from(name),
+			filter: filter.clone(),
+			format: format.clone(),
+			output: output.clone(),
 		}
 	}
 
+	pub fn enabled(&self, level: Level) -> bool {
+		self.min_level.is_some_and(|min_level| level >= min_level)
+	}
+
+	pub fn event<'a>(&'a self, level: Level, msg: &'a str) -> Event<'a> {
+		Event {
+			logger: self,
+			level,
+			msg: Cow::Borrowed(msg),
+			fields: Vec::new(),
+		}
+	}
+
+	/// Only builds the message if `level` is enabled, for messages which need formatting.
+	pub fn event_with(&self, level: Level, msg: impl FnOnce() -> String) -> Event<'_> {
+		let msg = match self.enabled(level) {
+			true => Cow::Owned(msg()),
+			false => Cow::Borrowed(""),
+		};
+
+		Event {
+			logger: self,
+			level,
+			msg,
+			fields: Vec::new(),
+		}
+	}
+
+	pub fn trace<'a>(&'a self, msg: &'a str) -> Event<'a> {
+		self.event(Level::Trace, msg)
+	}
+
+	pub fn debug<'a>(&'a self, msg: &'a str) -> Event<'a> {
+		self.event(Level::Debug, msg)
+	}
+
+	pub fn info<'a>(&'a self, msg: &'a str) -> Event<'a> {
+		self.event(Level::Info, msg)
+	}
+
+	pub fn warn<'a>(&'a self, msg: &'a str) -> Event<'a> {
+		self.event(Level::Warn, msg)
+	}
+
+	pub fn error<'a>(&'a self, msg: &'a str) -> Event<'a> {
+		self.event(Level::Error, msg)
+	}
+
 	pub fn at(&self, level: Level, msg: &str) {
-		let now = Utc::now();
-		let Logger { name } = self;
-		println!("{now} {level} {name} {msg}")
+		self.event(level, msg);
+	}
+
+	fn write(&self, level: Level, msg: &str, fields: &[(&'static str, FieldValue)]) {
+		let record = Record {
+			time: Utc::now(),
+			level,
+			name: &self.name,
+			msg,
+			fields,
+		};
+
+		self.output.write(&*self.format, &record);
 	}
 
 	pub fn err_at(&self, err: impl Error, level: Level, msg: &str) {
+		if !self.enabled(level) {
+			return;
+		}
+
 		let mut msg = String::from(msg);
 
 		let mut err: &dyn Error = &err;
@@ -105,3 +235,146 @@ impl Logger {
 		self.err_at(err, Level::Error, msg)
 	}
 }
+
+impl Debug for Logger {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Logger")
+			.field("name", &self.name)
+			.field("output", &self.output)
+			.finish_non_exhaustive()
+	}
+}
+
+impl Event<'_> {
+	pub fn field(mut self, key: &'static str, value: impl Into<FieldValue>) -> Self {
+		if self.logger.enabled(self.level) {
+			self.fields.push((key, value.into()));
+		}
+		self
+	}
+
+	/// Only builds the value if the level of the event is enabled, e.g. for
+	/// [`FieldValue::display`].
+	pub fn field_with<V: Into<FieldValue>>(
+		mut self,
+		key: &'static str,
+		value: impl FnOnce() -> V,
+	) -> Self {
+		if self.logger.enabled(self.level) {
+			self.fields.push((key, value().into()));
+		}
+		self
+	}
+}
+
+impl Drop for Event<'_> {
+	fn drop(&mut self) {
+		if self.logger.enabled(self.level) {
+			self.logger.write(self.level, &self.msg, &self.fields);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::Buffer;
+
+	#[test]
+	fn filters_per_child() {
+		// Arrange
+		let buffer = Buffer::default();
+		let filter = "lemonade.db=debug,warn".parse().unwrap();
+		let logger = Logger::new("lemonade")
+			.with_filter(filter)
+			.with_output(Output::writer(buffer.clone()));
+		let db = logger.child("db");
+
+		// Act
+		logger.at_info("hidden");
+		logger.at_warn("shown");
+		db.at_trace("hidden");
+		db.at_debug("shown");
+
+		// Assert
+		let lines = buffer.lines();
+		let lines = lines
+			.iter()
+			.map(|line| line.split_once(" UTC ").unwrap().1)
+			.collect::<Vec<_>>();
+		assert_eq!(lines, ["WARN lemonade shown", "DEBUG lemonade.db shown"]);
+	}
+
+	#[test]
+	fn disabled_levels_skip_formatting() {
+		// Arrange
+		let buffer = Buffer::default();
+		let logger = Logger::new("lemonade")
+			.with_filter(Filter::new(Level::Info))
+			.with_output(Output::writer(buffer.clone()));
+		let formatted = std::cell::Cell::new(0);
+		let format = |value: &str| {
+			formatted.set(formatted.get() + 1);
+			value.to_string()
+		};
+
+		// Act
+		logger
+			.event_with(Level::Debug, || format("hidden"))
+			.field_with("id", || format("abc"));
+		logger
+			.event_with(Level::Info, || format("shown"))
+			.field_with("id", || format("abc"));
+
+		// Assert
+		let lines = buffer.lines();
+		let lines = lines
+			.iter()
+			.map(|line| line.split_once(" UTC ").unwrap().1)
+			.collect::<Vec<_>>();
+		assert_eq!(formatted.get(), 2);
+		assert_eq!(lines.len(), 1);
+		assert!(lines[0].starts_with("INFO lemonade shown"));
+	}
+
+	#[test]
+	fn children_share_output() {
+		// Arrange
+		let buffer = Buffer::default();
+		let logger = Logger::new("lemonade")
+			.with_filter(Filter::default())
+			.with_format(JsonFormat)
+			.with_output(Output::writer(buffer.clone()));
+
+		// Act
+		logger
+			.child("token")
+			.info("token refreshed")
+			.field("secret_id", "abc");
+		logger.at_warn("slow");
+
+		// Assert
+		let lines = buffer
+			.json_lines()
+			.into_iter()
+			.map(|line| {
+				(
+					line["logger"].clone(),
+					line["msg"].clone(),
+					line["secret_id"].clone(),
+				)
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(
+			lines,
+			[
+				(
+					"lemonade.token".into(),
+					"token refreshed".into(),
+					"abc".into()
+				),
+				("lemonade".into(), "slow".into(), serde_json::Value::Null),
+			]
+		);
+	}
+}
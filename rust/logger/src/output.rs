@@ -0,0 +1,72 @@
+use crate::{Format, Record};
+use std::fmt::{Debug, Formatter};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Where formatted lines are written to. Write errors are ignored, as there is nowhere left to
+/// report them.
+pub enum Output {
+	Stdout,
+	Stderr,
+	Writer(Mutex<Box<dyn Write + Send>>),
+	/// Dispatches `tracing` events targeted at the logger name instead, leaving formatting to the
+	/// subscriber.
+	#[cfg(feature = "tracing")]
+	Tracing,
+}
+
+impl Output {
+	/// Appends to the file at `path`, creating it if needed.
+	pub fn file(path: impl AsRef<Path>) -> std::io::Result<Output> {
+		let file = OpenOptions::new().create(true).append(true).open(path)?;
+		Ok(Output::writer(file))
+	}
+
+	pub fn writer(writer: impl Write + Send + 'static) -> Output {
+		Output::Writer(Mutex::new(Box::new(writer)))
+	}
+
+	pub(crate) fn write(&self, format: &dyn Format, record: &Record<'_>) {
+		#[cfg(feature = "tracing")]
+		if let Output::Tracing = self {
+			return crate::tracing_bridge::dispatch(record);
+		}
+
+		self.write_line(&format.format(record));
+	}
+
+	fn write_line(&self, line: &str) {
+		let _ = match self {
+			Output::Stdout => writeln!(std::io::stdout().lock(), "{line}"),
+			Output::Stderr => writeln!(std::io::stderr().lock(), "{line}"),
+			Output::Writer(writer) => {
+				let mut writer = writer
+					.lock()
+					.unwrap_or_else(|poisoned| poisoned.into_inner());
+				writeln!(writer, "{line}").and_then(|()| writer.flush())
+			}
+			#[cfg(feature = "tracing")]
+			Output::Tracing => Ok(()),
+		};
+	}
+}
+
+impl From<File> for Output {
+	fn from(file: File) -> Self {
+		Output::writer(file)
+	}
+}
+
+impl Debug for Output {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Output::Stdout => write!(f, "Stdout"),
+			Output::Stderr => write!(f, "Stderr"),
+			Output::Writer(_) => write!(f, "Writer"),
+			#[cfg(feature = "tracing")]
+			Output::Tracing => write!(f, "Tracing"),
+		}
+	}
+}
@@ -0,0 +1,96 @@
+use crate::Level;
+use chrono::{DateTime, Utc};
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+/// A single log line before formatting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record<'a> {
+	pub time: DateTime<Utc>,
+	pub level: Level,
+	pub name: &'a str,
+	pub msg: &'a str,
+	pub fields: &'a [(&'static str, FieldValue)],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+	Str(String),
+	Int(i64),
+	UInt(u64),
+	Float(f64),
+	Bool(bool),
+}
+
+impl FieldValue {
+	/// Captures anything displayable, e.g. ids wrapped in newtypes, as a string.
+	///
+	/// Formats right away, hence pass it to [`crate::Event::field_with`] where the level may be
+	/// disabled.
+	pub fn display(value: impl Display) -> FieldValue {
+		FieldValue::Str(value.to_string())
+	}
+}
+
+impl Display for FieldValue {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			FieldValue::Str(value) => write!(f, "{value}"),
+			FieldValue::Int(value) => write!(f, "{value}"),
+			FieldValue::UInt(value) => write!(f, "{value}"),
+			FieldValue::Float(value) => write!(f, "{value}"),
+			FieldValue::Bool(value) => write!(f, "{value}"),
+		}
+	}
+}
+
+impl From<&str> for FieldValue {
+	fn from(value: &str) -> Self {
+		FieldValue::Str(value.to_string())
+	}
+}
+
+impl From<String> for FieldValue {
+	fn from(value: String) -> Self {
+		FieldValue::Str(value)
+	}
+}
+
+impl From<Arc<str>> for FieldValue {
+	fn from(value: Arc<str>) -> Self {
+		FieldValue::Str(value.to_string())
+	}
+}
+
+impl From<bool> for FieldValue {
+	fn from(value: bool) -> Self {
+		FieldValue::Bool(value)
+	}
+}
+
+impl From<f64> for FieldValue {
+	fn from(value: f64) -> Self {
+		FieldValue::Float(value)
+	}
+}
+
+macro_rules! from_int {
+	($variant:ident: $($ty:ty),*) => {
+		$(
+			impl From<$ty> for FieldValue {
+				fn from(value: $ty) -> Self {
+					FieldValue::$variant(value.into())
+				}
+			}
+		)*
+	};
+}
+
+from_int!(Int: i8, i16, i32, i64);
+from_int!(UInt: u8, u16, u32, u64);
+
+impl From<usize> for FieldValue {
+	fn from(value: usize) -> Self {
+		FieldValue::UInt(value as u64)
+	}
+}
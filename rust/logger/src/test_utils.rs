@@ -0,0 +1,30 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// Collects the output of a logger, to be given as [`crate::Output::writer`].
+#[derive(Clone, Default)]
+pub(crate) struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Buffer {
+	pub(crate) fn lines(&self) -> Vec<String> {
+		let output = String::from_utf8(self.0.lock().unwrap().clone()).unwrap();
+		output.lines().map(String::from).collect()
+	}
+
+	pub(crate) fn json_lines(&self) -> Vec<serde_json::Value> {
+		self.lines()
+			.iter()
+			.map(|line| serde_json::from_str(line).unwrap())
+			.collect()
+	}
+}
+
+impl Write for Buffer {
+	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+		self.0.lock().unwrap().write(buf)
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> {
+		Ok(())
+	}
+}
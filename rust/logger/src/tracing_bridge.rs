@@ -0,0 +1,489 @@
+use crate::{FieldValue, Filter, Format, Level, Output, Record, TextFormat};
+use chrono::Utc;
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, OnceLock, RwLock};
+use tracing_core::callsite::Identifier;
+use tracing_core::field::{FieldSet, Value, Visit};
+use tracing_core::metadata::Kind;
+use tracing_core::subscriber::Interest;
+use tracing_core::{Callsite, Event, Field, LevelFilter, Metadata, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+/// Fields of a [`crate::Event`] beyond this are dropped when dispatched to `tracing`.
+const MAX_FIELDS: usize = 32;
+
+/// Callsites beyond this are not created, further combinations being dispatched through a
+/// callsite per level instead, targeted at [`OVERFLOW_TARGET`].
+const MAX_CALLSITES: usize = 1024;
+
+/// Events of overflowing callsites have the logger name and fields prepended and appended to the
+/// message.
+const OVERFLOW_TARGET: &str = "logger";
+
+/// Dispatches a record as a `tracing` event, targeted at the logger name.
+///
+/// `tracing` expects static metadata, hence a callsite is leaked for every combination of logger
+/// name, level and field keys, up to [`MAX_CALLSITES`]. These are few in practice, as they are
+/// fixed by the call sites.
+pub(crate) fn dispatch(record: &Record<'_>) {
+	let fields = &record.fields[..record.fields.len().min(MAX_FIELDS)];
+
+	let Some(callsite) = callsite(record.name, record.level, fields, true) else {
+		let callsite = callsite(OVERFLOW_TARGET, record.level, &[], false)
+			.expect("unbounded callsite should be created");
+		let mut msg = format!("{}: {}", record.name, record.msg);
+		for (key, value) in fields {
+			msg.push_str(&format!(" {key}={value}"));
+		}
+		return emit(callsite.metadata(), &msg, &[]);
+	};
+
+	emit(callsite.metadata(), record.msg, fields);
+}
+
+fn emit(metadata: &'static Metadata<'static>, msg: &str, fields: &[(&'static str, FieldValue)]) {
+	tracing_core::dispatcher::get_default(|dispatch| {
+		if !dispatch.enabled(metadata) {
+			return;
+		}
+
+		let field_set = metadata.fields();
+		let field_refs = field_set.iter().collect::<Vec<_>>();
+		let (message, field_refs) = field_refs
+			.split_first()
+			.expect("message is the first field");
+
+		let mut values: [(&Field, Option<&dyn Value>); MAX_FIELDS + 1] =
+			[(message, None); MAX_FIELDS + 1];
+		values[0] = (message, Some(&msg as &dyn Value));
+		for (i, (field, (_, value))) in field_refs.iter().zip(fields).enumerate() {
+			values[i + 1] = (field, Some(as_value(value)));
+		}
+
+		dispatch.event(&Event::new(metadata, &field_set.value_set(&values)));
+	});
+}
+
+fn as_value(value: &FieldValue) -> &dyn Value {
+	match value {
+		FieldValue::Str(value) => value,
+		FieldValue::Int(value) => value,
+		FieldValue::UInt(value) => value,
+		FieldValue::Float(value) => value,
+		FieldValue::Bool(value) => value,
+	}
+}
+
+struct BridgeCallsite {
+	metadata: OnceLock<Metadata<'static>>,
+}
+
+impl BridgeCallsite {
+	fn matches(&self, name: &str, level: Level, fields: &[(&'static str, FieldValue)]) -> bool {
+		let metadata = self.metadata();
+		let keys = metadata.fields().iter().skip(1);
+
+		metadata.target() == name
+			&& *metadata.level() == to_tracing_level(level)
+			&& keys
+				.map(|field| field.name())
+				.eq(fields.iter().map(|(key, _)| *key))
+	}
+}
+
+impl Callsite for BridgeCallsite {
+	fn set_interest(&self, _: Interest) {}
+
+	fn metadata(&self) -> &Metadata<'_> {
+		self.metadata.get().expect("set when leaked")
+	}
+}
+
+/// Leaked callsites, looked up by the hash of their name, level and field keys.
+#[derive(Default)]
+struct Callsites {
+	by_hash: HashMap<u64, Vec<&'static BridgeCallsite>>,
+	len: usize,
+}
+
+impl Callsites {
+	fn get(
+		&self,
+		hash: u64,
+		name: &str,
+		level: Level,
+		fields: &[(&'static str, FieldValue)],
+	) -> Option<&'static BridgeCallsite> {
+		self.by_hash
+			.get(&hash)?
+			.iter()
+			.copied()
+			.find(|callsite| callsite.matches(name, level, fields))
+	}
+}
+
+/// The callsite of `name`, `level` and the keys of `fields`, `None` if it does not exist yet and
+/// `bounded` callsites are exhausted.
+fn callsite(
+	name: &str,
+	level: Level,
+	fields: &[(&'static str, FieldValue)],
+	bounded: bool,
+) -> Option<&'static BridgeCallsite> {
+	static CALLSITES: OnceLock<RwLock<Callsites>> = OnceLock::new();
+	let callsites = CALLSITES.get_or_init(Default::default);
+
+	let mut hasher = DefaultHasher::new();
+	(name, level).hash(&mut hasher);
+	fields.iter().for_each(|(key, _)| key.hash(&mut hasher));
+	let hash = hasher.finish();
+
+	let existing = callsites
+		.read()
+		.expect("lock not poisoned")
+		.get(hash, name, level, fields);
+	if existing.is_some() {
+		return existing;
+	}
+
+	let callsite = {
+		let mut callsites = callsites.write().expect("lock not poisoned");
+		if let Some(callsite) = callsites.get(hash, name, level, fields) {
+			return Some(callsite);
+		}
+		if bounded && callsites.len >= MAX_CALLSITES {
+			return None;
+		}
+
+		let callsite = leak_callsite(name, level, fields);
+		callsites.by_hash.entry(hash).or_default().push(callsite);
+		callsites.len += 1;
+		callsite
+	};
+
+	// Registering calls into every subscriber, which may well log, hence not under the lock.
+	tracing_core::callsite::register(callsite);
+	Some(callsite)
+}
+
+fn leak_callsite(
+	name: &str,
+	level: Level,
+	fields: &[(&'static str, FieldValue)],
+) -> &'static BridgeCallsite {
+	let callsite: &'static BridgeCallsite = Box::leak(Box::new(BridgeCallsite {
+		metadata: OnceLock::new(),
+	}));
+
+	let target: &'static str = Box::leak(Box::from(name));
+	let field_names = std::iter::once("message").chain(fields.iter().map(|(key, _)| *key));
+	let field_names: &'static [&'static str] = Box::leak(field_names.collect());
+
+	let metadata = Metadata::new(
+		"logger event",
+		target,
+		to_tracing_level(level),
+		None,
+		None,
+		None,
+		FieldSet::new(field_names, Identifier(callsite)),
+		Kind::EVENT,
+	);
+	callsite
+		.metadata
+		.set(metadata)
+		.unwrap_or_else(|_| unreachable!("callsite just created"));
+
+	callsite
+}
+
+fn to_tracing_level(level: Level) -> tracing_core::Level {
+	match level {
+		Level::Trace => tracing_core::Level::TRACE,
+		Level::Debug => tracing_core::Level::DEBUG,
+		Level::Info => tracing_core::Level::INFO,
+		Level::Warn => tracing_core::Level::WARN,
+		Level::Error => tracing_core::Level::ERROR,
+	}
+}
+
+fn from_tracing_level(level: tracing_core::Level) -> Level {
+	match level {
+		tracing_core::Level::TRACE => Level::Trace,
+		tracing_core::Level::DEBUG => Level::Debug,
+		tracing_core::Level::INFO => Level::Info,
+		tracing_core::Level::WARN => Level::Warn,
+		_ => Level::Error,
+	}
+}
+
+/// A `tracing_subscriber` layer writing events the way a [`crate::Logger`] would, named after
+/// their target with `::` replaced by `.`, so that `LOG` directives apply to module paths too.
+pub struct LoggerLayer {
+	filter: Arc<Filter>,
+	format: Arc<dyn Format>,
+	output: Output,
+}
+
+impl LoggerLayer {
+	pub fn new() -> LoggerLayer {
+		LoggerLayer {
+			filter: Filter::shared_global(),
+			format: Arc::new(TextFormat),
+			output: Output::Stdout,
+		}
+	}
+
+	pub fn with_log_filter(mut self, filter: Filter) -> LoggerLayer {
+		self.filter = Arc::new(filter);
+		self
+	}
+
+	pub fn with_format(mut self, format: impl Format + 'static) -> LoggerLayer {
+		self.format = Arc::new(format);
+		self
+	}
+
+	/// # Panics
+	/// If `output` is [`Output::Tracing`], which would dispatch every event back to this layer.
+	pub fn with_output(mut self, output: Output) -> LoggerLayer {
+		assert!(
+			!matches!(output, Output::Tracing),
+			"layer should not output to tracing"
+		);
+
+		self.output = output;
+		self
+	}
+}
+
+impl LoggerLayer {
+	fn is_enabled(&self, name: &str, level: Level) -> bool {
+		self.filter
+			.min_level(name)
+			.is_some_and(|min_level| level >= min_level)
+	}
+}
+
+impl Default for LoggerLayer {
+	fn default() -> Self {
+		LoggerLayer::new()
+	}
+}
+
+impl<S: Subscriber> Layer<S> for LoggerLayer {
+	/// The filter never changes, hence whether a callsite is enabled is decided once for all.
+	fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+		match self.is_enabled(
+			&logger_name(metadata.target()),
+			from_tracing_level(*metadata.level()),
+		) {
+			true => Interest::always(),
+			false => Interest::never(),
+		}
+	}
+
+	fn enabled(&self, metadata: &Metadata<'_>, _: Context<'_, S>) -> bool {
+		self.is_enabled(
+			&logger_name(metadata.target()),
+			from_tracing_level(*metadata.level()),
+		)
+	}
+
+	fn max_level_hint(&self) -> Option<LevelFilter> {
+		let level_filter = match self.filter.lowest_level() {
+			Some(level) => LevelFilter::from_level(to_tracing_level(level)),
+			None => LevelFilter::OFF,
+		};
+
+		Some(level_filter)
+	}
+
+	fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+		let metadata = event.metadata();
+		let name = logger_name(metadata.target());
+		let level = from_tracing_level(*metadata.level());
+
+		if !self.is_enabled(&name, level) {
+			return;
+		}
+
+		let mut visitor = FieldVisitor::default();
+		event.record(&mut visitor);
+
+		self.output.write(
+			&*self.format,
+			&Record {
+				time: Utc::now(),
+				level,
+				name: &name,
+				msg: &visitor.msg,
+				fields: &visitor.fields,
+			},
+		);
+	}
+}
+
+/// The logger name of a `tracing` target, only allocated if it is a module path.
+fn logger_name(target: &str) -> Cow<'_, str> {
+	match target.contains("::") {
+		true => Cow::Owned(target.replace("::", ".")),
+		false => Cow::Borrowed(target),
+	}
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+	msg: String,
+	fields: Vec<(&'static str, FieldValue)>,
+}
+
+impl FieldVisitor {
+	fn push(&mut self, field: &Field, value: FieldValue) {
+		match field.name() {
+			"message" => self.msg = value.to_string(),
+			name => self.fields.push((name, value)),
+		}
+	}
+}
+
+impl Visit for FieldVisitor {
+	fn record_f64(&mut self, field: &Field, value: f64) {
+		self.push(field, value.into())
+	}
+
+	fn record_i64(&mut self, field: &Field, value: i64) {
+		self.push(field, value.into())
+	}
+
+	fn record_u64(&mut self, field: &Field, value: u64) {
+		self.push(field, value.into())
+	}
+
+	fn record_bool(&mut self, field: &Field, value: bool) {
+		self.push(field, value.into())
+	}
+
+	fn record_str(&mut self, field: &Field, value: &str) {
+		self.push(field, value.into())
+	}
+
+	fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+		self.push(field, FieldValue::Str(format!("{value:?}")))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_utils::Buffer;
+	use crate::{JsonFormat, Logger};
+	use tracing_subscriber::layer::SubscriberExt;
+
+	fn subscriber(buffer: &Buffer) -> impl Subscriber {
+		let layer = LoggerLayer::new()
+			.with_log_filter("debug".parse().unwrap())
+			.with_format(JsonFormat)
+			.with_output(Output::writer(buffer.clone()));
+
+		tracing_subscriber::registry().with(layer)
+	}
+
+	#[test]
+	fn layer_formats_tracing_events() {
+		// Arrange
+		let buffer = Buffer::default();
+
+		// Act
+		tracing::subscriber::with_default(subscriber(&buffer), || {
+			tracing::info!(target: "lemonade::db", attempt = 2, "connected");
+			tracing::trace!(target: "lemonade::db", "hidden");
+		});
+
+		// Assert
+		let lines = buffer.json_lines();
+		assert_eq!(lines.len(), 1);
+		assert_eq!(lines[0]["logger"], "lemonade.db");
+		assert_eq!(lines[0]["level"], "INFO");
+		assert_eq!(lines[0]["msg"], "connected");
+		assert_eq!(lines[0]["attempt"], 2);
+	}
+
+	#[test]
+	fn layer_disables_filtered_callsites() {
+		// Arrange
+		let filtered = tracing_subscriber::registry()
+			.with(LoggerLayer::new().with_log_filter("warn,lemonade.db=debug".parse().unwrap()));
+		let disabled = tracing_subscriber::registry()
+			.with(LoggerLayer::new().with_log_filter("off".parse().unwrap()));
+
+		// Act
+		let max_level = filtered.max_level_hint();
+		let disabled_max_level = disabled.max_level_hint();
+		let (db_debug, db_trace, api_debug) = tracing::subscriber::with_default(filtered, || {
+			(
+				tracing::enabled!(target: "lemonade::db", tracing::Level::DEBUG),
+				tracing::enabled!(target: "lemonade::db::pool", tracing::Level::TRACE),
+				tracing::enabled!(target: "lemonade::api", tracing::Level::DEBUG),
+			)
+		});
+
+		// Assert
+		assert!(db_debug);
+		assert!(!db_trace);
+		assert!(!api_debug);
+		assert_eq!(max_level, Some(LevelFilter::DEBUG));
+		assert_eq!(disabled_max_level, Some(LevelFilter::OFF));
+	}
+
+	#[test]
+	fn logger_emits_through_tracing() {
+		// Arrange
+		let buffer = Buffer::default();
+		let logger = Logger::new("lemonade")
+			.with_filter(Filter::new(Level::Trace))
+			.with_output(Output::Tracing)
+			.child("token");
+
+		// Act
+		tracing::subscriber::with_default(subscriber(&buffer), || {
+			logger
+				.info("token refreshed")
+				.field("secret_id", "abc")
+				.field("expires_in", 3600);
+			logger.at_trace("hidden by the layer");
+		});
+
+		// Assert
+		let lines = buffer.json_lines();
+		assert_eq!(lines.len(), 1);
+		assert_eq!(lines[0]["logger"], "lemonade.token");
+		assert_eq!(lines[0]["msg"], "token refreshed");
+		assert_eq!(lines[0]["secret_id"], "abc");
+		assert_eq!(lines[0]["expires_in"], 3600);
+	}
+
+	#[test]
+	fn callsites_are_reused_per_keys() {
+		// Arrange
+		let fields = [("secret_id", FieldValue::from("abc"))];
+		let other_fields = [("secret_id", FieldValue::from("def"))];
+		let other_keys = [("expires_in", FieldValue::from(3600))];
+
+		// Act
+		let first = callsite("lemonade.reuse", Level::Info, &fields, true).unwrap();
+		let same_keys = callsite("lemonade.reuse", Level::Info, &other_fields, true).unwrap();
+		let different_keys = callsite("lemonade.reuse", Level::Info, &other_keys, true).unwrap();
+		let different_level = callsite("lemonade.reuse", Level::Warn, &fields, true).unwrap();
+
+		// Assert
+		assert!(std::ptr::eq(first, same_keys));
+		assert!(!std::ptr::eq(first, different_keys));
+		assert!(!std::ptr::eq(first, different_level));
+	}
+}
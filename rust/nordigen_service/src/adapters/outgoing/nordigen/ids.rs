@@ -1,63 +1,18 @@
-use constrained_str::{constrained_str, Validator};
-use std::convert::Infallible;
+use constrained_str::ConstrainedStr;
+use std::sync::Arc;
 
-pub struct NordigenApiAccountIdValidator;
+// Nordigen does not document the format of its identifiers, hence every str is accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ConstrainedStr)]
+pub struct NordigenApiAccountId(Arc<str>);
 
-impl Validator for NordigenApiAccountIdValidator {
-	type Error = Infallible;
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ConstrainedStr)]
+pub struct NordigenApiInstitutionId(Arc<str>);
 
-	fn validate(v: &str) -> Result<&str, Self::Error> {
-		// every str is valid account id
-		Ok(v)
-	}
-}
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ConstrainedStr)]
+pub struct NordigenApiRequisitionId(Arc<str>);
 
-pub struct NordigenApiInstitutionIdValidator;
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ConstrainedStr)]
+pub struct NordigenApiTransactionId(Arc<str>);
 
-impl Validator for NordigenApiInstitutionIdValidator {
-	type Error = Infallible;
-
-	fn validate(v: &str) -> Result<&str, Self::Error> {
-		// every str is valid institution id
-		Ok(v)
-	}
-}
-
-pub struct NordigenApiRequisitionIdValidator;
-
-impl Validator for NordigenApiRequisitionIdValidator {
-	type Error = Infallible;
-
-	fn validate(v: &str) -> Result<&str, Self::Error> {
-		// every str is valid requisition id
-		Ok(v)
-	}
-}
-
-pub struct NordigenApiTransactionIdValidator;
-
-impl Validator for NordigenApiTransactionIdValidator {
-	type Error = Infallible;
-
-	fn validate(v: &str) -> Result<&str, Self::Error> {
-		// every str is valid requisition id
-		Ok(v)
-	}
-}
-
-pub struct NordigenApiReferenceValidator;
-
-impl Validator for NordigenApiReferenceValidator {
-	type Error = Infallible;
-
-	fn validate(v: &str) -> Result<&str, Self::Error> {
-		// every str is valid requisition id
-		Ok(v)
-	}
-}
-
-constrained_str!(pub NordigenApiAccountId, NordigenApiAccountIdValidator);
-constrained_str!(pub NordigenApiInstitutionId, NordigenApiInstitutionIdValidator);
-constrained_str!(pub NordigenApiRequisitionId, NordigenApiRequisitionIdValidator);
-constrained_str!(pub NordigenApiTransactionId, NordigenApiTransactionIdValidator);
-constrained_str!(pub NordigenApiReference, NordigenApiReferenceValidator);
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ConstrainedStr)]
+pub struct NordigenApiReference(Arc<str>);
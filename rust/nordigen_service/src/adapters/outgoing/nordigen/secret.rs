@@ -1,26 +1,11 @@
-use constrained_str::{constrained_str, Validator};
-use std::convert::Infallible;
+use constrained_str::ConstrainedStr;
+use std::sync::Arc;
 
-pub struct NordigenApiSecretIdValidator;
-impl Validator for NordigenApiSecretIdValidator {
-	type Error = Infallible;
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ConstrainedStr)]
+pub struct NordigenApiSecretId(Arc<str>);
 
-	fn validate(v: &str) -> Result<&str, Self::Error> {
-		Ok(v)
-	}
-}
-
-pub struct NordigenApiSecretKeyValidator;
-impl Validator for NordigenApiSecretKeyValidator {
-	type Error = Infallible;
-
-	fn validate(v: &str) -> Result<&str, Self::Error> {
-		Ok(v)
-	}
-}
-
-constrained_str!(pub NordigenApiSecretId, NordigenApiSecretIdValidator);
-constrained_str!(pub NordigenApiSecretKey, NordigenApiSecretKeyValidator);
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ConstrainedStr)]
+pub struct NordigenApiSecretKey(Arc<str>);
 
 pub struct Secret {
 	pub id: NordigenApiSecretId,
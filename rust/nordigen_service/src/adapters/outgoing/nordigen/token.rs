@@ -1,27 +1,12 @@
 use chrono::{DateTime, Utc};
-use constrained_str::{constrained_str, Validator};
-use std::convert::Infallible;
+use constrained_str::ConstrainedStr;
+use std::sync::Arc;
 
-pub struct NordigenApiAccessTokenValidator;
-impl Validator for NordigenApiAccessTokenValidator {
-	type Error = Infallible;
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ConstrainedStr)]
+pub struct NordigenApiAccessToken(Arc<str>);
 
-	fn validate(v: &str) -> Result<&str, Self::Error> {
-		Ok(v)
-	}
-}
-
-pub struct NordigenApiRefreshTokenValidator;
-impl Validator for NordigenApiRefreshTokenValidator {
-	type Error = Infallible;
-
-	fn validate(v: &str) -> Result<&str, Self::Error> {
-		Ok(v)
-	}
-}
-
-constrained_str!(pub NordigenApiAccessToken, NordigenApiAccessTokenValidator);
-constrained_str!(pub NordigenApiRefreshToken, NordigenApiRefreshTokenValidator);
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ConstrainedStr)]
+pub struct NordigenApiRefreshToken(Arc<str>);
 
 #[derive(Debug, Clone)]
 pub struct Expiring<T> {
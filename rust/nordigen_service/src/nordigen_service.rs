@@ -5,9 +5,9 @@ use crate::adapters::outgoing::nordigen::{
 };
 use async_stream::try_stream;
 use chrono::{DateTime, NaiveDate, Utc};
-use error::HttpError;
+use error::{HttpError, ProblemDetails};
 use futures::Stream;
-use http::Response;
+use http::StatusCode;
 use std::fmt::Debug;
 use sync_utils::PrimedWatch;
 use thiserror::Error;
@@ -161,15 +161,12 @@ pub enum NordigenServiceError<E> {
 	},
 }
 
-impl<E: HttpError<Body>, Body: From<&'static str>> HttpError<Body> for NordigenServiceError<E> {
-	fn into_response(self) -> http::response::Response<Body> {
+impl<E: HttpError> HttpError for NordigenServiceError<E> {
+	fn problem_details(&self) -> ProblemDetails {
 		match self {
-			NordigenServiceError::Api(api_error) => api_error.into_response(),
+			NordigenServiceError::Api(api_error) => api_error.problem_details(),
 			NordigenServiceError::ExpiredAccessToken { .. } => {
-				let mut response = Response::new("an internal server occurred".into());
-				*response.status_mut() = http::StatusCode::INTERNAL_SERVER_ERROR;
-
-				response
+				ProblemDetails::new(StatusCode::INTERNAL_SERVER_ERROR)
 			}
 		}
 	}
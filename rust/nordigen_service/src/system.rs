@@ -5,14 +5,13 @@ use crate::adapters::outgoing::nordigen::{
 use crate::nordigen_service::NordigenService;
 use async_stream::try_stream;
 use chrono::NaiveDate;
-use constrained_str::{constrained_str, Validator};
+use constrained_str::ConstrainedStr;
 use error::InfallibleResultExt;
 use futures::Stream;
 use iban::Iban;
 use iso_country::Country;
 use iso_currency::Currency;
 use rust_decimal::Decimal;
-use std::convert::Infallible;
 use std::fmt::Debug;
 use std::sync::Arc;
 use sync_utils::PrimedWatch;
@@ -130,67 +129,17 @@ pub struct Transaction {
 	date: NaiveDate,
 }
 
-pub struct AccountIdValidator;
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ConstrainedStr)]
+struct AccountId(Arc<str>);
 
-impl Validator for AccountIdValidator {
-	type Error = Infallible;
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ConstrainedStr)]
+struct TransactionId(Arc<str>);
 
-	fn validate(v: &str) -> Result<&str, Self::Error> {
-		// every str is valid account id
-		Ok(v)
-	}
-}
-
-constrained_str!(AccountId, AccountIdValidator);
-
-pub struct TransactionIdValidator;
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ConstrainedStr)]
+struct LinkId(Arc<str>);
 
-impl Validator for TransactionIdValidator {
-	type Error = Infallible;
-
-	fn validate(v: &str) -> Result<&str, Self::Error> {
-		// every str is valid account id
-		Ok(v)
-	}
-}
-
-constrained_str!(TransactionId, TransactionIdValidator);
-
-pub struct LinkIdValidator;
-
-impl Validator for LinkIdValidator {
-	type Error = Infallible;
-
-	fn validate(v: &str) -> Result<&str, Self::Error> {
-		// every str is valid account id
-		Ok(v)
-	}
-}
-
-constrained_str!(LinkId, LinkIdValidator);
-
-pub struct ClientIdValidator;
-
-impl Validator for ClientIdValidator {
-	type Error = Infallible;
-
-	fn validate(v: &str) -> Result<&str, Self::Error> {
-		// every str is valid account id
-		Ok(v)
-	}
-}
-
-constrained_str!(ClientId, ClientIdValidator);
-
-pub struct InstitutionIdValidator;
-
-impl Validator for InstitutionIdValidator {
-	type Error = Infallible;
-
-	fn validate(v: &str) -> Result<&str, Self::Error> {
-		// every str is valid account id
-		Ok(v)
-	}
-}
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ConstrainedStr)]
+struct ClientId(Arc<str>);
 
-constrained_str!(InstitutionId, InstitutionIdValidator);
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, ConstrainedStr)]
+struct InstitutionId(Arc<str>);
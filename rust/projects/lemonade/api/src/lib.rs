@@ -3,10 +3,12 @@ use axum::extract::FromRequestParts;
 use axum::http::request::Parts;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
+use error::Context;
 use lemonade_db::{Connection, Database};
 use lemonade_model::{BasicCredential, SessionToken};
 use serde::{Deserialize, Serialize};
 use snafu::{Report, ResultExt, Whatever};
+use std::error::Error;
 use std::str::FromStr;
 use uuid::Uuid;
 
@@ -62,6 +64,14 @@ impl From<Whatever> for ApiError {
 	}
 }
 
+impl<E: Error + 'static> From<Context<E>> for ApiError {
+	fn from(error: Context<E>) -> Self {
+		let report = snafu::Report::from_error(error);
+		tracing::warn!("{report}");
+		Self::InternalServerError
+	}
+}
+
 struct Authentication {
 	user_id: Uuid,
 }
@@ -132,7 +142,7 @@ impl FromRequestParts<AppState> for Authentication {
 		let mut conn = state.db.conn().await?;
 
 		if let Some(value) = auth_header.strip_prefix("Basic ") {
-			return Ok(Authentication::from_basic(&mut conn, value).await?);
+			return Authentication::from_basic(&mut conn, value).await;
 		}
 
 		if let Some(value) = auth_header.strip_prefix("Session ") {
@@ -9,7 +9,7 @@
 //! to refactor into the relevant modules herewithin. Any consequent internal API changes should
 //! (hopefully) be caught by the compiler.
 
-use snafu::{ResultExt, Whatever};
+use error::{Context, ContextExt};
 use sqlx::pool::PoolConnection;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::{PgConnection, PgPool, Postgres};
@@ -18,37 +18,40 @@ use std::ops::{Deref, DerefMut};
 mod tables;
 pub(crate) mod types;
 
+/// A failed query, annotated with what was attempted.
+pub type DatabaseError = Context<sqlx::Error>;
+
 #[derive(Debug, Clone)]
 pub struct Database {
 	connection_pool: PgPool,
 }
 
 impl Database {
-	pub async fn new(url: &str) -> Result<Self, Whatever> {
+	pub async fn new(url: &str) -> Result<Self, Context<sqlx::Error>> {
 		let connection_pool = PgPoolOptions::new()
 			.connect(url)
 			.await
-			.with_whatever_context(|_| "unable to create database, unable to connect")?;
+			.context("unable to create database, unable to connect")?;
 
 		Ok(Database { connection_pool })
 	}
 
-	pub async fn conn(&self) -> Result<Connection<'static>, Whatever> {
+	pub async fn conn(&self) -> Result<Connection<'static>, Context<sqlx::Error>> {
 		let conn = self
 			.connection_pool
 			.acquire()
 			.await
-			.with_whatever_context(|_| "")?;
+			.context("unable to acquire connection from pool")?;
 
-		Ok(Connection(InnerConnection::Pooled(conn)))
+		Ok(Connection(InnerConnection::Pooled(Box::new(conn))))
 	}
 
-	pub async fn begin_transaction(&self) -> Result<Transaction, Whatever> {
+	pub async fn begin_transaction(&self) -> Result<Transaction, Context<sqlx::Error>> {
 		let tx = self
 			.connection_pool
 			.begin()
 			.await
-			.with_whatever_context(|_| "")?;
+			.context("unable to begin transaction")?;
 
 		Ok(Transaction(tx))
 	}
@@ -57,12 +60,15 @@ impl Database {
 pub struct Transaction(sqlx::Transaction<'static, Postgres>);
 
 impl Transaction {
-	pub fn conn(&mut self) -> Connection {
+	pub fn conn(&mut self) -> Connection<'_> {
 		Connection(InnerConnection::Normal(self.0.deref_mut()))
 	}
 
-	pub async fn commit_transaction(self) -> Result<(), Whatever> {
-		self.0.commit().await.with_whatever_context(|_| "")
+	pub async fn commit_transaction(self) -> Result<(), Context<sqlx::Error>> {
+		self.0
+			.commit()
+			.await
+			.context("unable to commit transaction")
 	}
 }
 
@@ -82,7 +88,7 @@ impl<'l> DerefMut for Connection<'l> {
 	}
 }
 enum InnerConnection<'l> {
-	Pooled(PoolConnection<Postgres>),
+	Pooled(Box<PoolConnection<Postgres>>),
 	Normal(&'l mut PgConnection),
 }
 
@@ -91,7 +97,7 @@ impl<'l> Deref for InnerConnection<'l> {
 	fn deref(&self) -> &Self::Target {
 		match self {
 			InnerConnection::Pooled(conn) => conn.deref(),
-			InnerConnection::Normal(conn) => &*conn,
+			InnerConnection::Normal(conn) => conn,
 		}
 	}
 }
@@ -100,7 +106,7 @@ impl<'l> DerefMut for InnerConnection<'l> {
 	fn deref_mut(&mut self) -> &mut Self::Target {
 		match self {
 			InnerConnection::Pooled(conn) => conn.deref_mut(),
-			InnerConnection::Normal(conn) => &mut *conn,
+			InnerConnection::Normal(conn) => conn,
 		}
 	}
 }
@@ -1,9 +1,9 @@
-use crate::{Connection, Database};
+use crate::{Connection, Database, DatabaseError};
 use async_stream::try_stream;
 use chrono::{DateTime, Utc};
+use error::{Context, ContextExt};
 use futures::Stream;
 use lemonade_model::{AccessToken, ExpiresAt, Expiring, RefreshToken, SecretId};
-use snafu::{whatever, ResultExt, Whatever};
 use sqlx::postgres::PgListener;
 use std::ops::DerefMut;
 
@@ -11,38 +11,29 @@ impl Database {
 	pub fn observe_access_token<'l>(
 		&'l self,
 		secret_id: &'l SecretId,
-	) -> impl Stream<Item = Result<Option<Expiring<AccessToken>>, ()>> + 'l + Send {
+	) -> impl Stream<Item = Result<Option<Expiring<AccessToken>>, DatabaseError>> + 'l + Send {
 		try_stream! {
 			let mut listener = PgListener::connect_with(&self.connection_pool)
-			.await
-			.with_whatever_context::<_, _, Whatever>(|_| {
-				format!(
-					"failed to keep access token for secret id {} updated, failed to create listener",
-					secret_id.0
-				)
-			}).map_err(|_| ())?;
+				.await
+				.with_context(|| format!("failed to keep access token for secret id {} updated, failed to create listener", secret_id.0))?;
 
 			listener.listen("nordigen_tokens")
 				.await
-				.with_whatever_context::<_, _, Whatever>(|_| format!("failed to keep access token for secret id {} updated, failed to register to channel", secret_id.0))
-				.map_err(|_| ())?;
+				.with_context(|| format!("failed to keep access token for secret id {} updated, failed to register to channel", secret_id.0))?;
 
 			loop {
 				let new_access_token = self.conn()
 					.await
-					.with_whatever_context::<_, _, Whatever>(|_| "")
-					.map_err(|_| ())?
+					.map_err(Context::into_inner)
+					.with_context(|| format!("failed to keep access token for secret id {} updated, failed to acquire connection", secret_id.0))?
 					.get_access_token(secret_id)
-					.await
-					.with_whatever_context::<_, _, Whatever>(|_| format!("failed to keep access token for secret id {} updated, failed to fetch access token", secret_id.0))
-					.map_err(|_| ())?;
+					.await?;
 
 				yield new_access_token;
 
 				let _ = listener.try_recv()
 					.await
-					.with_whatever_context::<_, _, Whatever>(|_| format!("failed to keep access token for secret id {} updated, failed to receive notification", secret_id.0))
-					.map_err(|_| ())?;
+					.with_context(|| format!("failed to keep access token for secret id {} updated, failed to receive notification", secret_id.0))?;
 			}
 		}
 	}
@@ -52,7 +43,7 @@ impl<'c> Connection<'c> {
 	async fn get_access_token(
 		&mut self,
 		secret_id: &SecretId,
-	) -> Result<Option<Expiring<AccessToken>>, Whatever> {
+	) -> Result<Option<Expiring<AccessToken>>, DatabaseError> {
 		let secret_id = &secret_id.0;
 
 		let access_token = sqlx::query_as(
@@ -61,14 +52,15 @@ impl<'c> Connection<'c> {
 			.bind(secret_id.as_ref())
 			.fetch_optional(self.deref_mut())
 			.await
-			.with_whatever_context(|_| {
+			.with_context(|| {
 				format!("failed to get access token for secret id {secret_id}, query failed")
 			})?
 			.map(|(access_token, expires_at): (Option<Box<str>>, Option<DateTime<Utc>>)| {
 				let (access_token, expires_at) = match (access_token, expires_at) {
 					(Some(access_token), Some(expires_at)) => (access_token, expires_at),
 					(None, None) => return Ok(None),
-					_ => whatever!("failed to get access token for secret id {secret_id}, expected both fields or none to be null"),
+					_ => return Err(sqlx::Error::Decode("expected both fields or none to be null".into()))
+						.with_context(|| format!("failed to get access token for secret id {secret_id}")),
 				};
 
 				Ok(Some(AccessToken(access_token.into()).expires_at(expires_at)))
@@ -77,14 +69,14 @@ impl<'c> Connection<'c> {
 		Ok(access_token)
 	}
 
-	pub async fn create_token(&mut self, secret_id: &SecretId) -> Result<(), Whatever> {
+	pub async fn create_token(&mut self, secret_id: &SecretId) -> Result<(), DatabaseError> {
 		let secret_id = &secret_id.0;
 
 		sqlx::query("INSERT INTO nordigen_tokens VALUES ($1, NULL, NULL, NULL, NULL) ON CONFLICT DO NOTHING")
 			.bind(secret_id.as_ref())
 			.execute(self.deref_mut())
 			.await
-			.with_whatever_context(|_| format!("failed to update token for secret with id {secret_id}, failed to ensure row exists for secret"))?;
+			.with_context(|| format!("failed to update token for secret with id {secret_id}, failed to ensure row exists for secret"))?;
 
 		Ok(())
 	}
@@ -92,7 +84,7 @@ impl<'c> Connection<'c> {
 	pub async fn get_token_for_update(
 		&mut self,
 		secret_id: &SecretId,
-	) -> Result<Option<Option<(Expiring<AccessToken>, Expiring<RefreshToken>)>>, Whatever> {
+	) -> Result<Option<Option<(Expiring<AccessToken>, Expiring<RefreshToken>)>>, DatabaseError> {
 		let secret_id = &secret_id.0;
 
 		let row =
@@ -100,7 +92,7 @@ impl<'c> Connection<'c> {
 				.bind(secret_id.as_ref())
 				.fetch_optional(self.deref_mut())
 				.await
-				.with_whatever_context(|_| format!("failed to update token for secret with id {secret_id}, failed to get row"))?;
+				.with_context(|| format!("failed to update token for secret with id {secret_id}, failed to get row"))?;
 
 		let Some((access, access_expiry, refresh, refresh_expiry)) = row else {
 			// Other replica holds lock on row and is currently performing update
@@ -113,7 +105,8 @@ impl<'c> Connection<'c> {
 		let (access, access_expiry, refresh, refresh_expiry): (Box<str>, DateTime<Utc>, Box<str>, DateTime<Utc>) = match (access, access_expiry, refresh, refresh_expiry) {
 			(Some(access), Some(access_expiry), Some(refresh), Some(refresh_expiry)) => (access, access_expiry, refresh, refresh_expiry),
 			(None, None, None, None) => return Ok(Some(None)),
-			_ => whatever!("failed to update token for secret with id {secret_id}, expected either all or none of the fields to be null"),
+			_ => return Err(sqlx::Error::Decode("expected either all or none of the fields to be null".into()))
+				.with_context(|| format!("failed to update token for secret with id {secret_id}")),
 		};
 
 		let access_token = AccessToken(access.into()).expires_at(access_expiry);
@@ -127,7 +120,7 @@ impl<'c> Connection<'c> {
 		secret_id: &SecretId,
 		access: Expiring<AccessToken>,
 		refresh: Expiring<RefreshToken>,
-	) -> Result<(), Whatever> {
+	) -> Result<(), DatabaseError> {
 		let secret_id = &secret_id.0;
 		let (access, access_expires_at) = access.into_parts();
 		let (refresh, refresh_expires_at) = refresh.into_parts();
@@ -139,7 +132,7 @@ impl<'c> Connection<'c> {
 			.bind(refresh_expires_at)
 			.bind(secret_id.as_ref())
 			.execute(self.deref_mut())
-			.await.with_whatever_context(|_| format!("failed to update token for secret with id {secret_id}, failed to update row with new values"))?;
+			.await.with_context(|| format!("failed to update token for secret with id {secret_id}, failed to update row with new values"))?;
 
 		Ok(())
 	}
@@ -1,18 +1,26 @@
 use axum::Router;
+use error::{Context, ContextExt};
+use future_utils::extensions::CancellationTokenExt;
+use future_utils::{RestartStrategy, Shutdown, Supervisor};
 use futures::TryStreamExt;
 use lemonade_api::AppState;
-use lemonade_db::Database;
+use lemonade_db::{Database, DatabaseError};
 use lemonade_model::{AccessToken, Expiring, RefreshToken, Secret, SecretId, SecretKey};
 use lemonade_nordigen::NordigenClient;
+use logger::LoggerLayer;
 use reqwest::Url;
-use snafu::{Report, ResultExt, Whatever};
+use snafu::{Report, Whatever};
+use std::convert::Infallible;
+use std::future::IntoFuture;
 use std::sync::{Arc, RwLock};
 use tokio::net::TcpListener;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 
 #[tokio::main]
 async fn main() {
-	tracing_subscriber::FmtSubscriber::builder()
-		.with_max_level(tracing::Level::INFO)
+	tracing_subscriber::registry()
+		.with(LoggerLayer::new())
 		.init();
 
 	let db = Database::new("postgresql://localhost:5432/lemonade")
@@ -37,151 +45,179 @@ async fn main() {
 	let nordigen_client =
 		NordigenClient::new(Url::parse("https://bankaccountdata.gocardless.com/api/v2/").unwrap());
 
-	tokio::spawn({
-		let db = db.clone();
-		let access_token = access_token.clone();
-		let secret_id = &secret.id;
-
+	let shutdown = Shutdown::new().cancel_on_signals();
+
+	let supervisor = Supervisor::new(RestartStrategy::OneForOne)
+		.with_cancellation_token(shutdown.token().child_token())
+		.with_on_error(|name, delay, error| {
+			tracing::warn!("{name} failed, restarting in {delay:?}\nerror cause: {error}")
+		})
+		.with_child("access token observer", {
+			let db = db.clone();
+			let access_token = access_token.clone();
+			let secret_id = &secret.id;
+
+			move |_| observe_access_token(db.clone(), secret_id, access_token.clone())
+		})
+		.with_child("token updater", {
+			let db = db.clone();
+			let updater = Updater(nordigen_client);
+			let secret: &Secret = secret;
+
+			move |_| update_tokens_periodically(db.clone(), updater.clone(), secret)
+		})
+		.start();
+
+	shutdown.spawn("background tasks", supervisor.join());
+
+	let server = axum::serve(listener, app).with_graceful_shutdown(shutdown.cancelled());
+	shutdown.spawn("http server", {
+		let token = shutdown.token().clone();
 		async move {
-			let res = db
-				.observe_access_token(secret_id)
-				.try_for_each(|new_access_token| async {
-					{
-						let mut access_token = access_token.write().expect("lock not poisoned");
-						tracing::info!("got new access token {:?}", new_access_token);
-						*access_token = new_access_token;
-					}
-
-					Ok(())
-				})
-				.await;
-
-			if let Err(_) = res {
-				tracing::error!("observe access token failed");
+			if let Err(err) = token.cancel_when_done(server.into_future()).await {
+				tracing::error!("http server failed: {err}");
 			}
 		}
 	});
 
-	tokio::spawn({
-		let db = db.clone();
-		let nordigen_client = nordigen_client.clone();
-		let secret: &Secret = secret;
-
-		struct Updater(NordigenClient);
-
-		impl Updater {
-			async fn update(
-				&self,
-				secret: &Secret,
-				current_tokens: Option<&(Expiring<AccessToken>, Expiring<RefreshToken>)>,
-			) -> Result<(Expiring<AccessToken>, Expiring<RefreshToken>), Whatever> {
-				let Some((current_expiring_access, current_expiring_refresh)) = current_tokens
-				else {
-					return self.0.new_token(secret).await.with_whatever_context(|_| format!("failed to update tokens for secret id {}, failed to fetch new token pair when none existed", secret.id.0));
-				};
-
-				let Some(current_refresh) = current_expiring_refresh.as_ref() else {
-					// current refresh token is expired, fetch entirely new token pair
-					return self.0.new_token(secret).await.with_whatever_context(|_| format!("failed to update tokens for secret id {}, failed to fetch new token when current refresh token was expired", secret.id.0));
-				};
-
-				let Some(_) = current_expiring_access.as_ref() else {
-					// current access token is expired, fetch new one
-					let new_access = self.0.refresh_token(current_refresh).await?;
-
-					return Ok((new_access, current_expiring_refresh.clone()));
-				};
-
-				// Both current access and refresh token are still valid, no need to fetch new
-				Ok((
-					current_expiring_access.clone(),
-					current_expiring_refresh.clone(),
-				))
+	let report = shutdown.drain().await;
+	if !report.is_clean() {
+		tracing::warn!("{report}");
+	}
+}
+
+#[derive(Clone)]
+struct Updater(NordigenClient);
+
+impl Updater {
+	async fn update(
+		&self,
+		secret: &Secret,
+		current_tokens: Option<&(Expiring<AccessToken>, Expiring<RefreshToken>)>,
+	) -> Result<(Expiring<AccessToken>, Expiring<RefreshToken>), Context<Whatever>> {
+		let Some((current_expiring_access, current_expiring_refresh)) = current_tokens else {
+			return self.0.new_token(secret).await.with_context(|| format!("failed to update tokens for secret id {}, failed to fetch new token pair when none existed", secret.id.0));
+		};
+
+		let Some(current_refresh) = current_expiring_refresh.as_ref() else {
+			// current refresh token is expired, fetch entirely new token pair
+			return self.0.new_token(secret).await.with_context(|| format!("failed to update tokens for secret id {}, failed to fetch new token when current refresh token was expired", secret.id.0));
+		};
+
+		let Some(_) = current_expiring_access.as_ref() else {
+			// current access token is expired, fetch new one
+			let new_access = self
+				.0
+				.refresh_token(current_refresh)
+				.await
+				.with_context(|| {
+					format!(
+						"failed to update tokens for secret id {}, failed to refresh access token",
+						secret.id.0
+					)
+				})?;
+
+			return Ok((new_access, current_expiring_refresh.clone()));
+		};
+
+		// Both current access and refresh token are still valid, no need to fetch new
+		Ok((
+			current_expiring_access.clone(),
+			current_expiring_refresh.clone(),
+		))
+	}
+}
+
+async fn observe_access_token(
+	db: Database,
+	secret_id: &SecretId,
+	access_token: Arc<RwLock<Option<Expiring<AccessToken>>>>,
+) -> Result<(), DatabaseError> {
+	db.observe_access_token(secret_id)
+		.try_for_each(|new_access_token| async {
+			{
+				let mut access_token = access_token.write().expect("lock not poisoned");
+				tracing::info!("got new access token {:?}", new_access_token);
+				*access_token = new_access_token;
 			}
+
+			Ok(())
+		})
+		.await
+}
+
+async fn update_tokens_periodically(
+	db: Database,
+	updater: Updater,
+	secret: &Secret,
+) -> Result<(), Infallible> {
+	loop {
+		tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+
+		let mut tx = match db
+			.begin_transaction()
+			.await
+			.context("failed to update tokens, failed to begin transaction")
+		{
+			Ok(tx) => tx,
+			Err(err) => {
+				tracing::warn!("{}", Report::from_error(err));
+				continue;
+			}
+		};
+
+		let mut conn = tx.conn();
+
+		if let Err(err) = conn
+			.create_token(&secret.id)
+			.await
+			.context("failed to update tokens, failed to create token pair")
+		{
+			tracing::warn!("{}", Report::from_error(err));
+			continue;
 		}
 
-		let updater = Updater(nordigen_client);
+		let pair = match conn
+			.get_token_for_update(&secret.id)
+			.await
+			.context("failed to update tokens, failed to lock token pair")
+		{
+			Ok(Some(pair)) => pair,
+			Ok(None) => {
+				tracing::info!("other replica is holding lock on token pair");
+				continue;
+			}
+			Err(err) => {
+				tracing::warn!("{}", Report::from_error(err));
+				continue;
+			}
+		};
 
-		async move {
-			loop {
-				tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-
-				let mut tx = match db
-					.begin_transaction()
-					.await
-					.with_whatever_context::<_, _, Whatever>(|_| "")
-				{
-					Ok(tx) => tx,
-					Err(err) => {
-						tracing::warn!("{}", Report::from_error(err));
-						continue;
-					}
-				};
-
-				let mut conn = tx.conn();
-
-				if let Err(err) = conn
-					.create_token(&secret.id)
-					.await
-					.with_whatever_context::<_, _, Whatever>(|_| "")
-				{
-					tracing::warn!("{}", Report::from_error(err));
-					continue;
-				}
-
-				let pair = match conn
-					.get_token_for_update(&secret.id)
-					.await
-					.with_whatever_context::<_, _, Whatever>(|_| "")
-				{
-					Ok(Some(pair)) => pair,
-					Ok(None) => {
-						tracing::info!("other replica is holding lock on token pair");
-						continue;
-					}
-					Err(err) => {
-						tracing::warn!("{}", Report::from_error(err));
-						continue;
-					}
-				};
-
-				let new_pair = match updater
-					.update(secret, pair.as_ref())
-					.await
-					.with_whatever_context::<_, _, Whatever>(|_| "")
-				{
-					Ok(new_pair) => new_pair,
-					Err(err) => {
-						tracing::warn!("{}", Report::from_error(err));
-						continue;
-					}
-				};
-
-				if pair.as_ref() == Some(&new_pair) {
-					tracing::info!("token pair not changed, skipping update");
-					continue;
-				}
-
-				let (access, refresh) = new_pair;
-
-				if let Err(err) = conn.update_token(&secret.id, access, refresh).await {
-					tracing::warn!("{}", Report::from_error(err));
-					continue;
-				}
-
-				if let Err(err) = tx
-					.commit_transaction()
-					.await
-					.with_whatever_context::<_, _, Whatever>(|_| "")
-				{
-					tracing::warn!("{}", Report::from_error(err));
-					continue;
-				}
-
-				tracing::info!("token pair updated");
+		let new_pair = match updater.update(secret, pair.as_ref()).await {
+			Ok(new_pair) => new_pair,
+			Err(err) => {
+				tracing::warn!("{}", Report::from_error(err));
+				continue;
 			}
+		};
+
+		if pair.as_ref() == Some(&new_pair) {
+			tracing::info!("token pair not changed, skipping update");
+			continue;
+		}
+
+		let (access, refresh) = new_pair;
+
+		if let Err(err) = conn.update_token(&secret.id, access, refresh).await {
+			tracing::warn!("{}", Report::from_error(err));
+			continue;
+		}
+
+		if let Err(err) = tx.commit_transaction().await {
+			tracing::warn!("{}", Report::from_error(err));
+			continue;
 		}
-	});
 
-	axum::serve(listener, app).await.unwrap();
+		tracing::info!("token pair updated");
+	}
 }
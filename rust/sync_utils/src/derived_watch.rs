@@ -0,0 +1,346 @@
+use crate::watch::State;
+use crate::{Versioned, Watch};
+use futures::future::BoxFuture;
+use futures::{FutureExt, Stream};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// A read-only view of one or more watches, created by [`Watch::map`] and [`Watch::zip`].
+///
+/// The value is derived lazily, i.e. whenever it is read rather than whenever the underlying
+/// watches are updated. It changes whenever any underlying watch is updated, its version being the
+/// sum of their versions.
+pub struct DerivedWatch<T> {
+	source: Arc<dyn Source<T>>,
+}
+
+trait Source<T>: Send + Sync {
+	fn current(&self) -> Option<Versioned<T>>;
+
+	/// Completes on the next change after calling this function.
+	fn changed(&self) -> BoxFuture<'static, ()>;
+}
+
+impl<T> Clone for DerivedWatch<T> {
+	fn clone(&self) -> Self {
+		DerivedWatch {
+			source: self.source.clone(),
+		}
+	}
+}
+
+impl<S: Send + Sync + 'static> Watch<S> {
+	/// Derives a watch of `f` applied to the value of this one.
+	pub fn map<T>(&self, f: impl Fn(&S) -> T + Send + Sync + 'static) -> DerivedWatch<T> {
+		DerivedWatch {
+			source: Arc::new(MapWatch {
+				state: self.state.clone(),
+				f,
+			}),
+		}
+	}
+}
+
+impl<S: Clone + Send + Sync + 'static> Watch<S> {
+	/// Derives a watch of the values of this one and `other`, primed once both are.
+	pub fn zip<U: Clone + Send + Sync + 'static>(&self, other: &Watch<U>) -> DerivedWatch<(S, U)> {
+		DerivedWatch::from(self.clone()).zip(&DerivedWatch::from(other.clone()))
+	}
+}
+
+impl<T: Clone + Send + Sync + 'static> From<Watch<T>> for DerivedWatch<T> {
+	fn from(watch: Watch<T>) -> Self {
+		watch.map(T::clone)
+	}
+}
+
+impl<T: Send + 'static> DerivedWatch<T> {
+	pub fn latest(&self) -> Option<T> {
+		self.source.current().map(|versioned| versioned.value)
+	}
+
+	/// See [`Watch::version`].
+	pub fn version(&self) -> u64 {
+		self.source
+			.current()
+			.map_or(0, |versioned| versioned.version)
+	}
+
+	pub async fn primed(&self) -> PrimedDerivedWatch<T> {
+		self.wait_for(|_| true).await;
+
+		PrimedDerivedWatch {
+			watch: self.clone(),
+		}
+	}
+
+	/// Like [`Watch::changed`], though it always gives a value as the derived watch keeps the
+	/// underlying watches alive.
+	pub fn changed(&self) -> impl Future<Output = T> {
+		let changed = self.source.changed();
+		let source = self.source.clone();
+
+		async move {
+			changed.await;
+			let versioned = source.current().expect("changed watch is primed");
+			versioned.value
+		}
+	}
+
+	/// See [`Watch::wait_for`].
+	pub async fn wait_for(&self, mut predicate: impl FnMut(&T) -> bool) -> T {
+		loop {
+			let changed = self.source.changed();
+
+			if let Some(versioned) = self.source.current() {
+				if predicate(&versioned.value) {
+					return versioned.value;
+				}
+			}
+
+			changed.await;
+		}
+	}
+
+	/// See [`Watch::subscribe`]. Note that the stream keeps the underlying watches alive.
+	pub fn subscribe(&self) -> impl Stream<Item = Versioned<T>> + Send + 'static {
+		let source = self.source.clone();
+
+		futures::stream::unfold((source, 0), |(source, seen)| async move {
+			loop {
+				let changed = source.changed();
+
+				if let Some(versioned) = source.current() {
+					if versioned.version > seen {
+						let version = versioned.version;
+						return Some((versioned, (source, version)));
+					}
+				}
+
+				changed.await;
+			}
+		})
+	}
+
+	/// Derives a watch of `f` applied to the value of this one.
+	pub fn map<U>(&self, f: impl Fn(&T) -> U + Send + Sync + 'static) -> DerivedWatch<U> {
+		DerivedWatch {
+			source: Arc::new(MapDerived {
+				source: self.source.clone(),
+				f,
+			}),
+		}
+	}
+
+	/// Derives a watch of the values of this one and `other`, primed once both are.
+	pub fn zip<U: Send + 'static>(&self, other: &DerivedWatch<U>) -> DerivedWatch<(T, U)> {
+		DerivedWatch {
+			source: Arc::new(Zip {
+				left: self.source.clone(),
+				right: other.source.clone(),
+			}),
+		}
+	}
+}
+
+pub struct PrimedDerivedWatch<T> {
+	watch: DerivedWatch<T>,
+}
+
+impl<T> Clone for PrimedDerivedWatch<T> {
+	fn clone(&self) -> Self {
+		PrimedDerivedWatch {
+			watch: self.watch.clone(),
+		}
+	}
+}
+
+impl<T: Send + 'static> PrimedDerivedWatch<T> {
+	pub fn latest(&self) -> T {
+		self.watch.latest().expect("primed watch has a value")
+	}
+
+	pub fn version(&self) -> u64 {
+		self.watch.version()
+	}
+
+	/// See [`DerivedWatch::changed`].
+	pub fn changed(&self) -> impl Future<Output = T> {
+		self.watch.changed()
+	}
+
+	/// See [`Watch::wait_for`].
+	pub async fn wait_for(&self, predicate: impl FnMut(&T) -> bool) -> T {
+		self.watch.wait_for(predicate).await
+	}
+
+	/// See [`DerivedWatch::subscribe`].
+	pub fn subscribe(&self) -> impl Stream<Item = Versioned<T>> + Send + 'static {
+		self.watch.subscribe()
+	}
+}
+
+struct MapWatch<S, F> {
+	state: watch::Sender<State<S>>,
+	f: F,
+}
+
+impl<S, T, F> Source<T> for MapWatch<S, F>
+where
+	S: Send + Sync + 'static,
+	F: Fn(&S) -> T + Send + Sync,
+{
+	fn current(&self) -> Option<Versioned<T>> {
+		let state = self.state.borrow();
+		let value = state.value.as_ref()?;
+
+		Some(Versioned {
+			version: state.version,
+			value: (self.f)(value),
+		})
+	}
+
+	fn changed(&self) -> BoxFuture<'static, ()> {
+		let mut receiver = self.state.subscribe();
+
+		async move {
+			receiver
+				.changed()
+				.await
+				.expect("sender is owned by the derived watch");
+		}
+		.boxed()
+	}
+}
+
+struct MapDerived<T, F> {
+	source: Arc<dyn Source<T>>,
+	f: F,
+}
+
+impl<T, U, F> Source<U> for MapDerived<T, F>
+where
+	F: Fn(&T) -> U + Send + Sync,
+{
+	fn current(&self) -> Option<Versioned<U>> {
+		let versioned = self.source.current()?;
+
+		Some(Versioned {
+			version: versioned.version,
+			value: (self.f)(&versioned.value),
+		})
+	}
+
+	fn changed(&self) -> BoxFuture<'static, ()> {
+		self.source.changed()
+	}
+}
+
+struct Zip<T, U> {
+	left: Arc<dyn Source<T>>,
+	right: Arc<dyn Source<U>>,
+}
+
+impl<T, U> Source<(T, U)> for Zip<T, U> {
+	fn current(&self) -> Option<Versioned<(T, U)>> {
+		let left = self.left.current()?;
+		let right = self.right.current()?;
+
+		Some(Versioned {
+			version: left.version + right.version,
+			value: (left.value, right.value),
+		})
+	}
+
+	fn changed(&self) -> BoxFuture<'static, ()> {
+		let left = self.left.changed();
+		let right = self.right.changed();
+
+		async move {
+			futures::future::select(left, right).await;
+		}
+		.boxed()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::StreamExt;
+
+	#[tokio::test]
+	async fn map_derives_lazily() {
+		// Arrange
+		let watch = Watch::new();
+		let derived = watch.map(|value: &u32| value * 2);
+		let unprimed = derived.latest();
+
+		// Act
+		watch.update(1);
+		watch.update(2);
+
+		// Assert
+		assert_eq!(unprimed, None);
+		assert_eq!(derived.latest(), Some(4));
+		assert_eq!(derived.version(), 2);
+	}
+
+	#[tokio::test]
+	async fn zip_is_primed_once_both_are() {
+		// Arrange
+		let left = Watch::new();
+		let right = Watch::new();
+		let zipped = left.zip(&right);
+		let primed = tokio::spawn({
+			let zipped = zipped.clone();
+			async move { zipped.primed().await.latest() }
+		});
+
+		// Act
+		left.update("token");
+		tokio::task::yield_now().await;
+		let half_primed = zipped.latest();
+		right.update(3);
+
+		// Assert
+		assert_eq!(half_primed, None);
+		assert_eq!(primed.await.unwrap(), ("token", 3));
+	}
+
+	#[tokio::test]
+	async fn derived_changes_follow_base() {
+		// Arrange
+		let left = Watch::new();
+		let right = Watch::new();
+		left.update(1);
+		right.update(10);
+		let summed = left.zip(&right).map(|(left, right)| left + right);
+		let mut subscription = Box::pin(summed.subscribe());
+		let first = subscription.next().await;
+		let changed = summed.changed();
+
+		// Act
+		right.update(20);
+		let changed = changed.await;
+		left.update(2);
+		let second = subscription.next().await;
+
+		// Assert
+		assert_eq!(
+			first,
+			Some(Versioned {
+				version: 2,
+				value: 11
+			})
+		);
+		assert_eq!(changed, 21);
+		assert_eq!(
+			second,
+			Some(Versioned {
+				version: 4,
+				value: 22
+			})
+		);
+	}
+}
@@ -1,3 +1,7 @@
+mod derived_watch;
+mod refreshing_cell;
 mod watch;
 
+pub use derived_watch::*;
+pub use refreshing_cell::*;
 pub use watch::*;
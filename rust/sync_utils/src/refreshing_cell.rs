@@ -0,0 +1,318 @@
+use crate::Watch;
+use future_utils::restart_on_failure_with_backoff;
+use std::error::Error;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+type ErrorHandler = Arc<dyn Fn(Duration, &(dyn Error + 'static)) + Send + Sync>;
+
+/// A value as given by the fetcher of a [`RefreshingCell`], valid for `expires_in`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Refreshed<T> {
+	pub value: T,
+	pub expires_in: Duration,
+}
+
+/// Holds an expiring value, e.g. an access token, which is refreshed in the background before it
+/// expires.
+///
+/// Readers wait while there is no valid value, i.e. until the first fetch or, if refreshing keeps
+/// failing, until the next successful one. Failed fetches are retried with the default
+/// [`future_utils::BackoffPolicy`]. The background task is stopped once the cell is dropped.
+pub struct RefreshingCell<T> {
+	entry: Watch<Entry<T>>,
+	demand: Arc<Demand>,
+	task: JoinHandle<()>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry<T> {
+	value: T,
+	expires_at: Instant,
+	generation: u64,
+}
+
+/// The latest generation a reader asked to replace.
+struct Demand {
+	generation: AtomicU64,
+	notify: Notify,
+}
+
+#[derive(Clone)]
+pub struct RefreshingCellOptions {
+	refresh_at: f64,
+	on_error: ErrorHandler,
+}
+
+impl RefreshingCellOptions {
+	/// Refreshes once `fraction` of the lifetime of the value has passed, 0.8 unless configured.
+	///
+	/// # Panics
+	/// If `fraction` is not within `(0, 1]`.
+	pub fn with_refresh_at(mut self, fraction: f64) -> RefreshingCellOptions {
+		assert!(
+			fraction > 0.0 && fraction <= 1.0,
+			"refresh fraction should be within (0, 1]"
+		);
+
+		self.refresh_at = fraction;
+		self
+	}
+
+	/// Calls `on_error` with the delay before retrying and the error, every time fetching fails.
+	pub fn with_on_error(
+		mut self,
+		on_error: impl Fn(Duration, &(dyn Error + 'static)) + Send + Sync + 'static,
+	) -> RefreshingCellOptions {
+		self.on_error = Arc::new(on_error);
+		self
+	}
+}
+
+impl Default for RefreshingCellOptions {
+	fn default() -> Self {
+		RefreshingCellOptions {
+			refresh_at: 0.8,
+			on_error: Arc::new(|_, _| {}),
+		}
+	}
+}
+
+impl<T: Clone + Send + Sync + 'static> RefreshingCell<T> {
+	/// Spawns the background task fetching values with `fetcher`.
+	///
+	/// # Panics
+	/// If called outside of a tokio runtime.
+	pub fn new<F, Fut, E>(fetcher: F) -> RefreshingCell<T>
+	where
+		F: FnMut() -> Fut + Send + 'static,
+		Fut: Future<Output = Result<Refreshed<T>, E>> + Send + 'static,
+		E: Error + Send + 'static,
+	{
+		RefreshingCell::with_options(RefreshingCellOptions::default(), fetcher)
+	}
+
+	/// See [`RefreshingCell::new`].
+	pub fn with_options<F, Fut, E>(options: RefreshingCellOptions, fetcher: F) -> RefreshingCell<T>
+	where
+		F: FnMut() -> Fut + Send + 'static,
+		Fut: Future<Output = Result<Refreshed<T>, E>> + Send + 'static,
+		E: Error + Send + 'static,
+	{
+		let entry = Watch::new();
+		let demand = Arc::new(Demand {
+			generation: AtomicU64::new(0),
+			notify: Notify::new(),
+		});
+
+		let task = tokio::spawn(refresh(options, fetcher, entry.clone(), demand.clone()));
+
+		RefreshingCell {
+			entry,
+			demand,
+			task,
+		}
+	}
+
+	/// Gives the current value, waiting if it is not yet fetched or already expired.
+	pub async fn get(&self) -> T {
+		let entry = self
+			.entry
+			.wait_for(|entry| Instant::now() < entry.expires_at)
+			.await;
+
+		entry.value
+	}
+
+	/// Gives the current value if it is fetched and not expired.
+	pub fn try_get(&self) -> Option<T> {
+		self.entry
+			.latest()
+			.filter(|entry| Instant::now() < entry.expires_at)
+			.map(|entry| entry.value)
+	}
+
+	/// Refreshes the value ahead of time, e.g. because it got revoked, and gives the new one.
+	///
+	/// Demands coinciding with a refresh which is already in flight are served by it rather than
+	/// triggering another one.
+	pub async fn refresh(&self) -> T {
+		let generation = self.entry.latest().map_or(0, |entry| entry.generation);
+
+		self.demand
+			.generation
+			.fetch_max(generation, Ordering::SeqCst);
+		self.demand.notify.notify_one();
+
+		let entry = self
+			.entry
+			.wait_for(|entry| entry.generation > generation)
+			.await;
+
+		entry.value
+	}
+}
+
+impl<T> Drop for RefreshingCell<T> {
+	fn drop(&mut self) {
+		self.task.abort();
+	}
+}
+
+async fn refresh<T, F, Fut, E>(
+	options: RefreshingCellOptions,
+	mut fetcher: F,
+	entry: Watch<Entry<T>>,
+	demand: Arc<Demand>,
+) where
+	F: FnMut() -> Fut,
+	Fut: Future<Output = Result<Refreshed<T>, E>>,
+	E: Error + 'static,
+{
+	let mut generation = 0;
+
+	loop {
+		let Refreshed { value, expires_in } =
+			restart_on_failure_with_backoff(&mut fetcher, |delay, err| {
+				(options.on_error)(delay, &err)
+			})
+			.await;
+
+		let fetched_at = Instant::now();
+		generation += 1;
+		entry.update(Entry {
+			value,
+			expires_at: fetched_at + expires_in,
+			generation,
+		});
+
+		let refresh_at = fetched_at + expires_in.mul_f64(options.refresh_at);
+		loop {
+			tokio::select! {
+				_ = tokio::time::sleep_until(refresh_at) => break,
+				_ = demand.notify.notified() => {
+					if demand.generation.load(Ordering::SeqCst) >= generation {
+						break;
+					}
+				},
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::pin::Pin;
+	use std::sync::atomic::AtomicU32;
+
+	#[derive(Debug, thiserror::Error)]
+	#[error("fetch failed")]
+	struct FetchError;
+
+	type FetchFuture = Pin<Box<dyn Future<Output = Result<Refreshed<u32>, FetchError>> + Send>>;
+
+	fn counting_fetcher(
+		count: &Arc<AtomicU32>,
+		delay: Duration,
+	) -> impl FnMut() -> FetchFuture + Send + 'static {
+		let count = count.clone();
+		move || {
+			let count = count.clone();
+			Box::pin(async move {
+				tokio::time::sleep(delay).await;
+				let value = count.fetch_add(1, Ordering::SeqCst) + 1;
+				Ok(Refreshed {
+					value,
+					expires_in: Duration::from_secs(10),
+				})
+			})
+		}
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn readers_wait_for_first_value() {
+		// Arrange
+		let count = Arc::new(AtomicU32::new(0));
+		let cell = RefreshingCell::new(counting_fetcher(&count, Duration::from_secs(1)));
+		let before = cell.try_get();
+
+		// Act
+		let value = cell.get().await;
+
+		// Assert
+		assert_eq!(before, None);
+		assert_eq!(value, 1);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn refreshes_before_expiry() {
+		// Arrange
+		let count = Arc::new(AtomicU32::new(0));
+		let options = RefreshingCellOptions::default().with_refresh_at(0.5);
+		let cell = RefreshingCell::with_options(options, counting_fetcher(&count, Duration::ZERO));
+		cell.get().await;
+
+		// Act
+		tokio::time::sleep(Duration::from_secs(6)).await;
+		let value = cell.get().await;
+
+		// Assert
+		assert_eq!(value, 2);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn retries_failed_fetches() {
+		// Arrange
+		let attempts = Arc::new(AtomicU32::new(0));
+		let errors = Arc::new(AtomicU32::new(0));
+		let options = RefreshingCellOptions::default().with_on_error({
+			let errors = errors.clone();
+			move |_, _| {
+				errors.fetch_add(1, Ordering::SeqCst);
+			}
+		});
+		let cell = RefreshingCell::with_options(options, {
+			let attempts = attempts.clone();
+			move || {
+				let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+				async move {
+					match attempt {
+						0 | 1 => Err(FetchError),
+						_ => Ok(Refreshed {
+							value: "token",
+							expires_in: Duration::from_secs(10),
+						}),
+					}
+				}
+			}
+		});
+
+		// Act
+		let value = cell.get().await;
+
+		// Assert
+		assert_eq!(value, "token");
+		assert_eq!(errors.load(Ordering::SeqCst), 2);
+	}
+
+	#[tokio::test(start_paused = true)]
+	async fn coalesces_refresh_demands() {
+		// Arrange
+		let count = Arc::new(AtomicU32::new(0));
+		let cell = RefreshingCell::new(counting_fetcher(&count, Duration::from_secs(1)));
+		cell.get().await;
+
+		// Act
+		let (first, second, third) = tokio::join!(cell.refresh(), cell.refresh(), cell.refresh());
+
+		// Assert
+		assert_eq!((first, second, third), (2, 2, 2));
+		assert_eq!(count.load(Ordering::SeqCst), 2);
+	}
+}
@@ -1,10 +1,31 @@
-use std::sync::{Arc, RwLock};
-use tokio::sync::Notify;
+use futures::Stream;
+use std::future::Future;
+use tokio::sync::watch;
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct Watch<T> {
-	value: Arc<RwLock<Option<PrimedWatch<T>>>>,
-	notify: Arc<Notify>,
+	pub(crate) state: watch::Sender<State<T>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct State<T> {
+	pub(crate) value: Option<T>,
+	pub(crate) version: u64,
+}
+
+/// A value along with the number of updates which led to it, starting at 1 for the first value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Versioned<T> {
+	pub version: u64,
+	pub value: T,
+}
+
+impl<T> Clone for Watch<T> {
+	fn clone(&self) -> Self {
+		Watch {
+			state: self.state.clone(),
+		}
+	}
 }
 
 impl<T> Default for Watch<T> {
@@ -16,69 +37,238 @@ impl<T> Default for Watch<T> {
 impl<T> Watch<T> {
 	pub fn new() -> Watch<T> {
 		Watch {
-			value: Arc::new(RwLock::new(None)),
-			notify: Arc::new(Notify::new()),
+			state: watch::Sender::new(State {
+				value: None,
+				version: 0,
+			}),
 		}
 	}
 
 	pub fn update(&self, new_value: T) {
-		let mut inner_watch = self.value.write().expect("lock should not be poisoned");
-
-		if let Some(inner_watch) = inner_watch.as_ref() {
-			inner_watch.update(new_value);
-		} else {
-			*inner_watch = Some(PrimedWatch {
-				value: Arc::new(RwLock::new(new_value)),
-			});
-		}
-
-		self.notify.notify_waiters();
+		self.state.send_modify(|state| {
+			state.value = Some(new_value);
+			state.version += 1;
+		});
 	}
 
 	pub async fn primed(&self) -> PrimedWatch<T> {
-		loop {
-			let notification = self.notify.notified();
-
-			let inner_watch = self.value.read().expect("lock should not be poisoned");
+		let mut receiver = self.state.subscribe();
+		receiver
+			.wait_for(|state| state.value.is_some())
+			.await
+			.expect("sender is owned by self");
 
-			if let Some(inner_watch) = inner_watch.as_ref() {
-				return PrimedWatch {
-					value: inner_watch.value.clone(),
-				};
-			}
-
-			notification.await;
+		PrimedWatch {
+			watch: self.clone(),
 		}
 	}
+
+	/// The number of updates so far, 0 if not yet primed.
+	pub fn version(&self) -> u64 {
+		self.state.borrow().version
+	}
 }
 
 impl<T: Clone> Watch<T> {
 	pub fn latest(&self) -> Option<T> {
-		let inner_watch = self.value.read().expect("lock should not be poisoned");
+		self.state.borrow().value.clone()
+	}
 
-		let Some(inner_watch) = inner_watch.as_ref() else {
-			return None;
-		};
+	/// Waits for the next update after calling this function and gives the new value, `None` if
+	/// every clone of the watch is dropped first.
+	pub fn changed(&self) -> impl Future<Output = Option<T>> {
+		let mut receiver = self.state.subscribe();
 
-		Some(inner_watch.latest())
+		async move {
+			receiver.changed().await.ok()?;
+
+			let state = receiver.borrow_and_update();
+			Some(state.value.clone().expect("updated watch is primed"))
+		}
+	}
+
+	/// Waits until the value satisfies `predicate`, which is checked against the current value first.
+	pub async fn wait_for(&self, mut predicate: impl FnMut(&T) -> bool) -> T {
+		let mut receiver = self.state.subscribe();
+		let state = receiver
+			.wait_for(|state| state.value.as_ref().is_some_and(&mut predicate))
+			.await
+			.expect("sender is owned by self");
+
+		state.value.clone().expect("checked by predicate")
+	}
+
+	/// Streams the current value, if any, followed by every update.
+	///
+	/// A subscriber which falls behind only sees the latest value, skipping intermediate ones, which
+	/// is noticeable by gaps in the versions. The stream ends once every clone of the watch is
+	/// dropped.
+	pub fn subscribe(&self) -> impl Stream<Item = Versioned<T>> + Send + Sync + 'static
+	where
+		T: Send + Sync + 'static,
+	{
+		let mut receiver = self.state.subscribe();
+		if receiver.borrow().value.is_some() {
+			receiver.mark_changed();
+		}
+
+		futures::stream::unfold(receiver, |mut receiver| async move {
+			receiver.changed().await.ok()?;
+
+			let versioned = {
+				let state = receiver.borrow_and_update();
+				Versioned {
+					version: state.version,
+					value: state.value.clone().expect("updated watch is primed"),
+				}
+			};
+
+			Some((versioned, receiver))
+		})
 	}
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct PrimedWatch<T> {
-	value: Arc<RwLock<T>>,
+	watch: Watch<T>,
+}
+
+impl<T> Clone for PrimedWatch<T> {
+	fn clone(&self) -> Self {
+		PrimedWatch {
+			watch: self.watch.clone(),
+		}
+	}
 }
 
 impl<T> PrimedWatch<T> {
 	pub fn update(&self, new_value: T) {
-		let mut value = self.value.write().expect("lock should not be poisoned");
-		*value = new_value;
+		self.watch.update(new_value)
+	}
+
+	pub fn version(&self) -> u64 {
+		self.watch.version()
 	}
 }
 
 impl<T: Clone> PrimedWatch<T> {
 	pub fn latest(&self) -> T {
-		let value = self.value.read().expect("lock should not be poisoned");
-		value.clone()
+		self.watch.latest().expect("primed watch has a value")
+	}
+
+	/// See [`Watch::changed`].
+	pub fn changed(&self) -> impl Future<Output = Option<T>> {
+		self.watch.changed()
+	}
+
+	/// See [`Watch::wait_for`].
+	pub async fn wait_for(&self, predicate: impl FnMut(&T) -> bool) -> T {
+		self.watch.wait_for(predicate).await
+	}
+
+	/// See [`Watch::subscribe`].
+	pub fn subscribe(&self) -> impl Stream<Item = Versioned<T>> + Send + Sync + 'static
+	where
+		T: Send + Sync + 'static,
+	{
+		self.watch.subscribe()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::StreamExt;
+
+	#[tokio::test]
+	async fn primed_waits_for_first_value() {
+		// Arrange
+		let watch = Watch::new();
+		let primed = tokio::spawn({
+			let watch = watch.clone();
+			async move { watch.primed().await.latest() }
+		});
+		tokio::task::yield_now().await;
+
+		// Act
+		watch.update(1);
+
+		// Assert
+		assert_eq!(primed.await.unwrap(), 1);
+	}
+
+	#[tokio::test]
+	async fn primed_updates_notify() {
+		// Arrange
+		let watch = Watch::new();
+		watch.update(1);
+		let primed = watch.primed().await;
+		let changed = watch.changed();
+
+		// Act
+		primed.update(2);
+
+		// Assert
+		assert_eq!(changed.await, Some(2));
+		assert_eq!(watch.version(), 2);
+	}
+
+	#[tokio::test]
+	async fn changed_ends_once_watch_is_dropped() {
+		// Arrange
+		let watch = Watch::<u32>::new();
+		let changed = watch.changed();
+
+		// Act
+		drop(watch);
+
+		// Assert
+		assert_eq!(changed.await, None);
+	}
+
+	#[tokio::test]
+	async fn wait_for_checks_current_value() {
+		// Arrange
+		let watch = Watch::new();
+		watch.update(3);
+
+		// Act
+		let value = watch.wait_for(|value| *value > 2).await;
+
+		// Assert
+		assert_eq!(value, 3);
+	}
+
+	#[tokio::test]
+	async fn subscribe_skips_intermediate_values() {
+		// Arrange
+		let watch = Watch::new();
+		watch.update("a");
+		let mut subscription = Box::pin(watch.subscribe());
+		let first = subscription.next().await;
+
+		// Act
+		watch.update("b");
+		watch.update("c");
+		let second = subscription.next().await;
+		drop(watch);
+		let end = subscription.next().await;
+
+		// Assert
+		assert_eq!(
+			first,
+			Some(Versioned {
+				version: 1,
+				value: "a"
+			})
+		);
+		assert_eq!(
+			second,
+			Some(Versioned {
+				version: 3,
+				value: "c"
+			})
+		);
+		assert_eq!(end, None);
 	}
 }
@@ -0,0 +1,55 @@
+use thiserror::Error;
+
+/// Command line flags, everything else is configured through the environment.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Args {
+	/// Sync once and exit, failing if any record failed.
+	pub once: bool,
+	/// Only print what would be published, once, without touching providers or the state.
+	pub dry_run: bool,
+}
+
+#[derive(Debug, Error)]
+#[error("unknown argument {0:?}, expected --once or --dry-run")]
+pub struct UnknownArgument(String);
+
+impl Args {
+	pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, UnknownArgument> {
+		let mut res = Args::default();
+
+		for arg in args {
+			match arg.as_str() {
+				"--once" => res.once = true,
+				"--dry-run" => res.dry_run = true,
+				_ => return Err(UnknownArgument(arg)),
+			}
+		}
+
+		Ok(res)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_flags() {
+		// Arrange
+		let args = ["--dry-run".to_string()];
+
+		// Act
+		let parsed = Args::parse(args).unwrap();
+		let unknown = Args::parse(["--twice".to_string()]);
+
+		// Assert
+		assert_eq!(
+			parsed,
+			Args {
+				once: false,
+				dry_run: true
+			}
+		);
+		assert!(unknown.is_err());
+	}
+}
@@ -1,74 +1,39 @@
-use std::env::VarError;
-use std::error::Error;
-use std::ffi::OsStr;
-use std::fmt::Display;
-use std::fs::File;
-use std::io::Read;
+use crate::records::Records;
+use config::Config;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
 
-#[derive(Debug)]
+#[derive(Debug, Config)]
 pub struct Config {
-	pub host: String,
-	pub domain: String,
-	pub password: String,
-	pub interval: String,
-}
-
-impl Config {
-	pub fn parse_from_env() -> Config {
-		Config {
-			host: get_env_var("HOST"),
-			domain: get_env_var("DOMAIN"),
-			password: get_file_env_var("PASSWORD_FILE"),
-			interval: get_env_var("INTERVAL"),
-		}
-	}
-}
-
-fn get_env_var(key: impl AsRef<OsStr>) -> String {
-	let err = match std::env::var(&key) {
-		Ok(res) => return res,
-		Err(err) => err,
-	};
-
-	match err {
-		VarError::NotPresent => panic!("missing environment var {:?}", key.as_ref()),
-		VarError::NotUnicode(s) => panic!(
-			"environment var {:?} not valid unicode, was {:?}",
-			key.as_ref(),
-			s
-		),
-	}
-}
-
-fn get_file_env_var(key: impl AsRef<OsStr>) -> String {
-	let mut res = String::new();
-	let path = get_env_var(key);
-	let mut file = match File::open(&path) {
-		Ok(file) => file,
-		Err(err) => exit_with_error(format!("could not open file ({path})"), err),
-	};
-	let _read_bytes = match file.read_to_string(&mut res) {
-		Ok(bytes) => bytes,
-		Err(err) => exit_with_error(format!("could not read from file ({path})"), err),
-	};
-
-	res.trim().to_string()
-}
-
-fn exit_with_error(msg: impl Display, e: impl Error) -> ! {
-	panic!("{msg}, due to:\n\n{}", format_error(e))
-}
-
-fn format_error(e: impl Error) -> String {
-	let mut res = format!(" - {}", e.to_string());
-
-	let mut source = e.source();
-
-	while let Some(e) = source {
-		res.push_str(&format!(", caused by\n - {}", e.to_string()));
-
-		source = e.source();
-	}
-
-	res.trim().to_string()
+	/// The records to keep updated, see [`RecordConfig`](crate::records::RecordConfig).
+	#[env_file = "RECORDS_FILE"]
+	pub records: Records,
+	#[env = "INTERVAL"]
+	#[validate(range(Duration::from_secs(1)..))]
+	pub interval: Duration,
+	#[env = "IP_LOOKUP_URL"]
+	#[default = "https://api.ipify.org"]
+	#[validate(regex = "^https?://")]
+	pub ip_lookup_url: String,
+	#[env = "IP6_LOOKUP_URL"]
+	#[default = "https://api6.ipify.org"]
+	#[validate(regex = "^https?://")]
+	pub ip6_lookup_url: String,
+	#[env = "STATE_FILE"]
+	#[default = "waypointer-state.json"]
+	pub state_file: PathBuf,
+	/// How long a published address is trusted before it is published again regardless.
+	#[env = "MAX_AGE"]
+	#[default = "1d"]
+	pub max_age: Duration,
+	/// Where to serve `/healthz`, `/readyz` and `/metrics`.
+	#[env = "LISTEN_ADDR"]
+	#[default = "0.0.0.0:8080"]
+	pub listen_addr: SocketAddr,
+	/// Syncs in a row that may fail before `/healthz` reports unhealthy.
+	#[env = "MAX_CONSECUTIVE_FAILURES"]
+	#[default = "3"]
+	#[validate(range(1..))]
+	pub max_consecutive_failures: u32,
 }
@@ -0,0 +1,406 @@
+use crate::state::StateError;
+use crate::sync::{Family, Outcome, SyncError, SyncReport};
+use axum::extract::State;
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+
+/// Tracks the outcomes of syncs for the `/healthz`, `/readyz` and `/metrics` endpoints.
+#[derive(Debug, Clone)]
+pub struct Health {
+	state: Arc<Mutex<HealthState>>,
+	max_consecutive_failures: u32,
+}
+
+#[derive(Debug, Default)]
+struct HealthState {
+	synced: bool,
+	/// Intervals in a row whose sync still failed after retrying, as any record failed or the
+	/// state could not be accessed.
+	consecutive_failures: u32,
+	records: BTreeMap<(String, Family), RecordMetrics>,
+	/// Sync attempts in which the address of a family could not be looked up, counted once
+	/// however many records publish it.
+	lookup_failures: BTreeMap<Family, u64>,
+	ips: BTreeMap<Family, IpAddr>,
+}
+
+#[derive(Debug, Default)]
+struct RecordMetrics {
+	attempted: u64,
+	succeeded: u64,
+	failed: u64,
+	/// When the record was last found or made up to date.
+	last_success: Option<DateTime<Utc>>,
+}
+
+impl Health {
+	/// Turns unhealthy once the syncs of `max_consecutive_failures` intervals in a row failed.
+	pub fn new(max_consecutive_failures: u32) -> Health {
+		Health {
+			state: Arc::default(),
+			max_consecutive_failures,
+		}
+	}
+
+	/// Counts the record updates and lookups of a sync, whether or not it is retried.
+	pub fn record_attempt(&self, res: &Result<SyncReport, StateError>, now: DateTime<Utc>) {
+		let mut state = self.state.lock().expect("health state is never poisoned");
+
+		let Ok(SyncReport { statuses, .. }) = res else {
+			return;
+		};
+
+		let mut failed_lookups = BTreeSet::new();
+		for status in statuses {
+			state.lookup_failures.entry(status.family).or_default();
+			let ip = match &status.result {
+				Ok(
+					Outcome::Unchanged(ip)
+					| Outcome::Published(ip, _)
+					| Outcome::WouldPublish(ip, _),
+				) => Some(*ip),
+				Err(SyncError::Publish(ip, _)) => Some(*ip),
+				Err(SyncError::Lookup(..)) => None,
+			};
+			if let Some(ip) = ip {
+				state.ips.insert(status.family, ip);
+			}
+
+			let metrics = state
+				.records
+				.entry((status.record.clone(), status.family))
+				.or_default();
+			match &status.result {
+				Ok(Outcome::Unchanged(_)) => metrics.last_success = Some(now),
+				Ok(Outcome::WouldPublish(..)) => {}
+				Ok(Outcome::Published(..)) => {
+					metrics.attempted += 1;
+					metrics.succeeded += 1;
+					metrics.last_success = Some(now);
+				}
+				Err(SyncError::Publish(..)) => {
+					metrics.attempted += 1;
+					metrics.failed += 1;
+				}
+				Err(SyncError::Lookup(family, _)) => {
+					failed_lookups.insert(*family);
+				}
+			}
+		}
+		for family in failed_lookups {
+			*state.lookup_failures.entry(family).or_default() += 1;
+		}
+	}
+
+	/// Records how the sync of an interval ended, after any retries.
+	pub fn record_interval(&self, succeeded: bool) {
+		let mut state = self.state.lock().expect("health state is never poisoned");
+		state.synced = true;
+		state.consecutive_failures = match succeeded {
+			true => 0,
+			false => state.consecutive_failures + 1,
+		};
+	}
+
+	pub fn is_healthy(&self) -> bool {
+		let state = self.state.lock().expect("health state is never poisoned");
+		state.consecutive_failures < self.max_consecutive_failures
+	}
+
+	/// Ready once the first sync completed, successfully or not.
+	pub fn is_ready(&self) -> bool {
+		let state = self.state.lock().expect("health state is never poisoned");
+		state.synced
+	}
+
+	/// Renders the metrics in the Prometheus text exposition format.
+	pub fn metrics(&self) -> String {
+		let state = self.state.lock().expect("health state is never poisoned");
+		let mut res = String::new();
+
+		counter(
+			&mut res,
+			&state,
+			"waypointer_updates_attempted_total",
+			"Updates of a record attempted, as its address changed or was due for a refresh.",
+			|metrics| metrics.attempted,
+		);
+		counter(
+			&mut res,
+			&state,
+			"waypointer_updates_succeeded_total",
+			"Updates of a record accepted by its provider.",
+			|metrics| metrics.succeeded,
+		);
+		counter(
+			&mut res,
+			&state,
+			"waypointer_updates_failed_total",
+			"Updates of a record that failed.",
+			|metrics| metrics.failed,
+		);
+
+		let name = "waypointer_lookup_failures_total";
+		header(
+			&mut res,
+			name,
+			"Sync attempts in which the public address of a family could not be looked up.",
+			"counter",
+		);
+		for (family, failures) in &state.lookup_failures {
+			writeln!(res, "{name}{{family=\"{family}\"}} {failures}").unwrap();
+		}
+
+		let name = "waypointer_last_success_timestamp_seconds";
+		header(
+			&mut res,
+			name,
+			"When a record was last found or made up to date, as Unix time.",
+			"gauge",
+		);
+		for ((record, family), metrics) in &state.records {
+			if let Some(last_success) = metrics.last_success {
+				let labels = record_labels(record, *family);
+				writeln!(res, "{name}{{{labels}}} {}", last_success.timestamp()).unwrap();
+			}
+		}
+
+		let name = "waypointer_current_ip";
+		header(
+			&mut res,
+			name,
+			"The address last looked up per family, as label of a constant 1.",
+			"gauge",
+		);
+		for (family, ip) in &state.ips {
+			writeln!(res, "{name}{{family=\"{family}\",ip=\"{ip}\"}} 1").unwrap();
+		}
+
+		let name = "waypointer_consecutive_failures";
+		header(
+			&mut res,
+			name,
+			"Intervals in a row whose sync failed.",
+			"gauge",
+		);
+		writeln!(res, "{name} {}", state.consecutive_failures).unwrap();
+
+		res
+	}
+
+	pub fn router(self) -> Router {
+		Router::new()
+			.route("/healthz", get(healthz))
+			.route("/readyz", get(readyz))
+			.route("/metrics", get(metrics))
+			.with_state(self)
+	}
+}
+
+async fn healthz(State(health): State<Health>) -> StatusCode {
+	status(health.is_healthy())
+}
+
+async fn readyz(State(health): State<Health>) -> StatusCode {
+	status(health.is_ready())
+}
+
+async fn metrics(State(health): State<Health>) -> impl IntoResponse {
+	(
+		[(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+		health.metrics(),
+	)
+}
+
+fn status(ok: bool) -> StatusCode {
+	match ok {
+		true => StatusCode::OK,
+		false => StatusCode::SERVICE_UNAVAILABLE,
+	}
+}
+
+fn counter(
+	res: &mut String,
+	state: &HealthState,
+	name: &str,
+	help: &str,
+	value: fn(&RecordMetrics) -> u64,
+) {
+	header(res, name, help, "counter");
+	for ((record, family), metrics) in &state.records {
+		let labels = record_labels(record, *family);
+		writeln!(res, "{name}{{{labels}}} {}", value(metrics)).unwrap();
+	}
+}
+
+fn header(res: &mut String, name: &str, help: &str, metric_type: &str) {
+	writeln!(res, "# HELP {name} {help}").unwrap();
+	writeln!(res, "# TYPE {name} {metric_type}").unwrap();
+}
+
+fn record_labels(record: &str, family: Family) -> String {
+	let record = record
+		.replace('\\', "\\\\")
+		.replace('"', "\\\"")
+		.replace('\n', "\\n");
+
+	format!("record=\"{record}\",type=\"{}\"", family.record_type())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ip_lookup::IpLookupError;
+	use crate::provider::DnsProviderError;
+	use crate::sync::RecordStatus;
+	use chrono::TimeZone;
+
+	fn synced(statuses: Vec<RecordStatus>) -> Result<SyncReport, StateError> {
+		Ok(SyncReport {
+			statuses,
+			save_error: None,
+		})
+	}
+
+	fn status(record: &str, result: Result<Outcome, SyncError>) -> RecordStatus {
+		RecordStatus {
+			record: record.to_string(),
+			family: Family::V4,
+			result,
+		}
+	}
+
+	fn rejected(ip: IpAddr) -> Result<Outcome, SyncError> {
+		Err(SyncError::Publish(
+			ip,
+			DnsProviderError::Rejected("fake rejection".to_string()),
+		))
+	}
+
+	#[test]
+	fn fails_after_consecutive_failures() {
+		// Arrange
+		let health = Health::new(2);
+		let ip = "203.0.113.7".parse().unwrap();
+		let now = Utc::now();
+
+		// Act
+		let initially_ready = health.is_ready();
+		health.record_attempt(&synced(vec![status("home", rejected(ip))]), now);
+		health.record_interval(false);
+		let after_one_failure = health.is_healthy();
+		health.record_attempt(&synced(vec![status("home", rejected(ip))]), now);
+		health.record_interval(false);
+		let after_two_failures = health.is_healthy();
+		health.record_attempt(
+			&synced(vec![status("home", Ok(Outcome::Unchanged(ip)))]),
+			now,
+		);
+		health.record_interval(true);
+		let after_success = health.is_healthy();
+
+		// Assert
+		assert!(!initially_ready);
+		assert!(health.is_ready());
+		assert!(after_one_failure);
+		assert!(!after_two_failures);
+		assert!(after_success);
+	}
+
+	#[test]
+	fn counts_lookup_failures_apart_from_updates() {
+		// Arrange
+		let health = Health::new(3);
+		let lookup_failed = || {
+			Err(SyncError::Lookup(
+				Family::V4,
+				Arc::new(IpLookupError::UnexpectedFamily(
+					"2001:db8::7".parse().unwrap(),
+				)),
+			))
+		};
+		let now = Utc::now();
+
+		// Act
+		health.record_attempt(
+			&synced(vec![
+				status("home", lookup_failed()),
+				status("www", lookup_failed()),
+			]),
+			now,
+		);
+		health.record_interval(false);
+
+		// Assert
+		let metrics = health.metrics();
+		assert!(metrics.contains("waypointer_lookup_failures_total{family=\"IPv4\"} 1\n"));
+		assert!(
+			metrics.contains("waypointer_updates_attempted_total{record=\"home\",type=\"A\"} 0\n")
+		);
+		assert!(metrics.contains("waypointer_updates_failed_total{record=\"www\",type=\"A\"} 0\n"));
+		assert!(metrics.contains("waypointer_consecutive_failures 1\n"));
+	}
+
+	#[test]
+	fn counts_failed_attempts_of_retried_sync() {
+		// Arrange
+		let health = Health::new(1);
+		let ip = "203.0.113.7".parse().unwrap();
+		let now = Utc::now();
+
+		// Act
+		health.record_attempt(&synced(vec![status("home", rejected(ip))]), now);
+		health.record_attempt(
+			&synced(vec![status(
+				"home",
+				Ok(Outcome::Published(ip, "ok".to_string())),
+			)]),
+			now,
+		);
+		health.record_interval(true);
+
+		// Assert
+		let metrics = health.metrics();
+		assert!(
+			metrics.contains("waypointer_updates_attempted_total{record=\"home\",type=\"A\"} 2\n")
+		);
+		assert!(
+			metrics.contains("waypointer_updates_succeeded_total{record=\"home\",type=\"A\"} 1\n")
+		);
+		assert!(metrics.contains("waypointer_updates_failed_total{record=\"home\",type=\"A\"} 1\n"));
+		assert!(metrics.contains("waypointer_consecutive_failures 0\n"));
+		assert!(health.is_healthy());
+	}
+
+	#[test]
+	fn renders_metrics() {
+		// Arrange
+		let health = Health::new(3);
+		let ip = "203.0.113.7".parse().unwrap();
+		let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+		health.record_attempt(
+			&synced(vec![
+				status(
+					"home.example.com (cloudflare)",
+					Ok(Outcome::Published(ip, "ok".to_string())),
+				),
+				status("www.example.com (namecheap)", rejected(ip)),
+			]),
+			now,
+		);
+		health.record_interval(false);
+
+		// Act
+		let metrics = health.metrics();
+
+		// Assert
+		assert_eq!(metrics, include_str!("../fixtures/metrics.txt"));
+	}
+}
@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode, Url};
+use std::net::IpAddr;
+use thiserror::Error;
+
+/// Finds out the public address of this host.
+#[async_trait]
+pub trait IpLookup: Send + Sync {
+	async fn lookup(&self) -> Result<IpAddr, IpLookupError>;
+}
+
+#[derive(Debug, Error)]
+pub enum IpLookupError {
+	#[error("request to IP lookup endpoint failed")]
+	Request(#[from] reqwest::Error),
+	#[error("IP lookup endpoint responded with status {0}")]
+	Status(u16),
+	#[error("IP lookup endpoint responded with invalid address {0:?}")]
+	InvalidAddress(String),
+	#[error("IP lookup endpoint responded with {0}, which is of the other address family")]
+	UnexpectedFamily(IpAddr),
+}
+
+/// Asks an endpoint which responds with the address of the caller as plain text, e.g.
+/// `https://api.ipify.org` for IPv4 or `https://api6.ipify.org` for IPv6.
+pub struct HttpIpLookup {
+	client: Client,
+	url: Url,
+}
+
+impl HttpIpLookup {
+	pub fn new(client: Client, url: Url) -> HttpIpLookup {
+		HttpIpLookup { client, url }
+	}
+}
+
+#[async_trait]
+impl IpLookup for HttpIpLookup {
+	async fn lookup(&self) -> Result<IpAddr, IpLookupError> {
+		let res = self.client.get(self.url.clone()).send().await?;
+
+		if res.status() != StatusCode::OK {
+			return Err(IpLookupError::Status(res.status().as_u16()));
+		}
+
+		let body = res.text().await?;
+		let body = body.trim();
+
+		body.parse()
+			.map_err(|_| IpLookupError::InvalidAddress(body.to_string()))
+	}
+}
+
+/// Stands in for the lookup endpoint in tests, giving whatever address it is set to.
+#[cfg(test)]
+pub struct FakeIpLookup {
+	pub ip: std::sync::Mutex<IpAddr>,
+}
+
+#[cfg(test)]
+impl FakeIpLookup {
+	pub fn new(ip: &str) -> FakeIpLookup {
+		FakeIpLookup {
+			ip: std::sync::Mutex::new(ip.parse().unwrap()),
+		}
+	}
+
+	pub fn set(&self, ip: &str) {
+		*self.ip.lock().unwrap() = ip.parse().unwrap();
+	}
+}
+
+#[cfg(test)]
+#[async_trait]
+impl IpLookup for FakeIpLookup {
+	async fn lookup(&self) -> Result<IpAddr, IpLookupError> {
+		Ok(*self.ip.lock().unwrap())
+	}
+}
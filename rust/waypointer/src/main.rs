@@ -1,111 +1,223 @@
+mod args;
 mod config;
-
-use std::error::Error;
-use std::fmt::{Display, Formatter};
-use std::thread;
-use std::time::Duration;
-use chrono::Utc;
-use reqwest::blocking::Client;
-use reqwest::{StatusCode, Url};
-use roxmltree::Document;
+mod health;
+mod ip_lookup;
+mod provider;
+mod records;
+mod state;
+mod sync;
+
+use crate::args::Args;
+use crate::health::Health;
+use crate::ip_lookup::HttpIpLookup;
+use crate::state::{StateError, StateFile};
+use crate::sync::{sync, Outcome, Record, SyncReport};
+use ::config::FromConfig;
+use chrono::{DateTime, TimeDelta, Utc};
 use config::Config;
+use error::{ErrorExt, ResultExt};
+use future_utils::extensions::CancellationTokenExt;
+use future_utils::{restart_on_failure_with_policy, BackoffPolicy, Shutdown};
+use reqwest::{Client, Url};
+use std::future::IntoFuture;
+use std::process::ExitCode;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::net::TcpListener;
+use tokio::time::{Instant, MissedTickBehavior};
+
+#[tokio::main]
+async fn main() -> ExitCode {
+	let args = Args::parse(std::env::args().skip(1)).must();
+	let config = Config::parse().must_report();
+	println!("{} | {config:?}", Utc::now());
+
+	let Config {
+		records,
+		interval,
+		ip_lookup_url,
+		ip6_lookup_url,
+		state_file,
+		max_age,
+		listen_addr,
+		max_consecutive_failures,
+	} = config;
+
+	let client = Client::new();
+	let records = records
+		.0
+		.iter()
+		.map(|record| record.build(&client))
+		.collect::<Result<Vec<_>, _>>()
+		.must();
+	println!(
+		"{} | keeping {} records updated: {:?}",
+		Utc::now(),
+		records.len(),
+		records.iter().map(|r| &r.name).collect::<Vec<_>>()
+	);
+
+	let syncer = Syncer {
+		lookup_v4: HttpIpLookup::new(client.clone(), Url::parse(&ip_lookup_url).must()),
+		lookup_v6: HttpIpLookup::new(client, Url::parse(&ip6_lookup_url).must()),
+		records,
+		state: StateFile::new(state_file),
+		max_age: TimeDelta::from_std(max_age).must(),
+		dry_run: args.dry_run,
+	};
+
+	if args.once || args.dry_run {
+		let res = syncer.sync(Utc::now()).await;
+		return match check(&res) {
+			Ok(()) => ExitCode::SUCCESS,
+			Err(err) => {
+				println!("{} | {}", Utc::now(), err.to_pretty_string());
+				ExitCode::FAILURE
+			}
+		};
+	}
+
+	let shutdown = Shutdown::new().cancel_on_signals();
+	let health = Health::new(max_consecutive_failures);
+
+	let listener = TcpListener::bind(listen_addr).await.must();
+	let server =
+		axum::serve(listener, health.clone().router()).with_graceful_shutdown(shutdown.cancelled());
+	shutdown.spawn("http server", {
+		let token = shutdown.token().clone();
+		async move {
+			if let Err(err) = token.cancel_when_done(server.into_future()).await {
+				println!("{} | http server failed: {err}", Utc::now());
+			}
+		}
+	});
+
+	shutdown.spawn("sync loop", {
+		let token = shutdown.token().clone();
+		async move {
+			token
+				.run_until_cancelled(sync_periodically(&syncer, &health, interval))
+				.await
+		}
+	});
+
+	let report = shutdown.drain().await;
+	if !report.is_clean() {
+		println!("{} | {report}", Utc::now());
+	}
+
+	ExitCode::SUCCESS
+}
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let config = Config::parse_from_env();
-    println!("{} | {config:?}", Utc::now());
-
-    let Config { host, domain, password, interval } = config;
-
-    let interval = interval.parse::<u64>().expect("configured interval must be valid u64");
-
-    let url = build_url(&host, &domain, &password);
-
-    let client = Client::new();
-
-    loop {
-        let res = update(&client, &url);
-        println!("{} | {res:?}", Utc::now());
-        thread::sleep(Duration::from_secs(interval));
-    }
+struct Syncer {
+	lookup_v4: HttpIpLookup,
+	lookup_v6: HttpIpLookup,
+	records: Vec<Record>,
+	state: StateFile,
+	max_age: TimeDelta,
+	dry_run: bool,
 }
 
-fn build_url(host: &str, domain: &str, password: &str) -> Url {
-    let mut url = Url::parse("https://dynamicdns.park-your-domain.com/update")
-        .expect("supplied base URL should be valid");
+#[derive(Debug, Error)]
+enum SyncFailed {
+	#[error("could not access state")]
+	State(#[from] StateError),
+	#[error("{failed} of {total} records failed")]
+	Records { failed: usize, total: usize },
+}
 
-    url.query_pairs_mut()
-        .append_pair("host", host)
-        .append_pair("domain", domain)
-        .append_pair("password", password);
+impl Syncer {
+	/// Syncs all records, printing the status of each.
+	async fn sync(&self, now: DateTime<Utc>) -> Result<SyncReport, StateError> {
+		let res = sync(
+			&self.lookup_v4,
+			&self.lookup_v6,
+			&self.records,
+			&self.state,
+			self.max_age,
+			self.dry_run,
+			now,
+		)
+		.await;
+
+		for status in res.iter().flat_map(|report| &report.statuses) {
+			let result = match &status.result {
+				Ok(Outcome::Unchanged(ip)) => format!("unchanged at {ip}"),
+				Ok(Outcome::Published(ip, res)) => format!("published {ip}, {res}"),
+				Ok(Outcome::WouldPublish(ip, description)) => {
+					format!("would publish {ip} by {description}")
+				}
+				Err(err) => format!("failed, {}", err.to_pretty_string()),
+			};
+			println!(
+				"{} | {} {}: {result}",
+				Utc::now(),
+				status.record,
+				status.family.record_type()
+			);
+		}
+
+		res
+	}
+}
 
-   url
+/// Syncs every `interval`, retrying failed syncs with backoff until the next one is due. A sync
+/// in progress is let finish, and only the outcome of the last attempt of each is recorded.
+async fn sync_periodically(syncer: &Syncer, health: &Health, interval: Duration) {
+	let mut ticks = tokio::time::interval(interval);
+	ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+	loop {
+		ticks.tick().await;
+		let next_sync = Instant::now() + interval;
+
+		let res = restart_on_failure_with_policy(
+			&BackoffPolicy::default(),
+			|| async {
+				let now = Utc::now();
+				let res = syncer.sync(now).await;
+				health.record_attempt(&res, now);
+
+				check(&res)
+			},
+			|_| Instant::now() < next_sync,
+			|delay, err| {
+				println!(
+					"{} | {}, retrying in {delay:?}",
+					Utc::now(),
+					err.to_pretty_string()
+				)
+			},
+		)
+		.await;
+
+		if let Err(err) = &res {
+			println!(
+				"{} | {}, retrying at the next sync",
+				Utc::now(),
+				err.to_pretty_string()
+			);
+		}
+		health.record_interval(res.is_ok());
+	}
 }
 
-fn update(client: &Client, url: &Url) -> Result<String, Box<dyn Error>> {
-    #[derive(Debug)]
-    struct Response {
-        description: String,
-        number: String,
-    }
-
-    impl Display for Response {
-        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-            write!(f, "[{number}] {desc}", number = self.number, desc = self.description)
-        }
-    }
-
-    let res = client.get(url.clone()).send()?;
-
-    if res.status() != StatusCode::OK {
-        return Err(format!("non-ok response, got {status}", status = res.status().as_u16()).into());
-    }
-
-    let body = res.text()?;
-
-    let document = Document::parse(&body)?;
-
-    let root = document.descendants().find(|n| n.has_tag_name("interface-response"))
-        .ok_or("root node missing")?;
-    let errors = root.children().find(|n| n.has_tag_name("errors"))
-        .map(|errors| errors.children()
-            .map(|error| error.text()
-                .map(|t| t.to_string())
-                .ok_or("text missing from error")
-            )
-            .collect::<Result<Vec<_>, _>>()
-        )
-        .ok_or("errors missing from response")??;
-    let responses = root.children().find(|n| n.has_tag_name("responses"))
-        .map(|responses| responses.children()
-            .map(|response| -> Result<Response, &'static str> {
-                let description = response.children().find(|n| n.has_tag_name("Description"))
-                    .map(|n| n.text().ok_or("response description elem missing text"))
-                    .ok_or("response missing description elem")??
-                    .to_string();
-
-                let number = response.children().find(|n| n.has_tag_name("ResponseNumber"))
-                    .map(|n| n.text().ok_or("response description elem missing text"))
-                    .ok_or("response missing description elem")??
-                    .to_string();
-
-                Ok(Response {
-                    description,
-                    number,
-                })
-            })
-            .collect::<Result<Vec<_>, _>>()
-        )
-        .ok_or("responses missing from response")??
-        .into_iter()
-        .map(|r| r.to_string()).collect::<Vec<_>>();
-
-    if !errors.is_empty() {
-        return Err(format!(
-            "errors: {}, responses: {}",
-            errors.join(", "),
-            responses.join(", ")
-        ).into());
-    }
-
-    Ok(format!("responses: {responses:?}"))
+fn check(res: &Result<SyncReport, StateError>) -> Result<(), SyncFailed> {
+	let SyncReport {
+		statuses,
+		save_error,
+	} = res.as_ref().map_err(|err| SyncFailed::State(err.clone()))?;
+
+	let failed = statuses.iter().filter(|s| s.result.is_err()).count();
+	if failed > 0 {
+		return Err(SyncFailed::Records {
+			failed,
+			total: statuses.len(),
+		});
+	}
+	if let Some(err) = save_error {
+		return Err(SyncFailed::State(err.clone()));
+	}
+
+	Ok(())
 }
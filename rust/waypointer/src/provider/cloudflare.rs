@@ -0,0 +1,216 @@
+use crate::provider::{DnsProvider, DnsProviderError};
+use async_trait::async_trait;
+use reqwest::Url;
+use reqwest::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+/// Cloudflare's v4 API, authenticated by an API token allowed to edit DNS records of the zone.
+///
+/// Updates the record of `name`, creating it if it does not exist yet.
+pub struct Cloudflare {
+	client: Client,
+	base_url: Url,
+	zone_id: String,
+	token: String,
+	name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+	success: bool,
+	#[serde(default)]
+	errors: Vec<ApiMessage>,
+	result: Option<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiMessage {
+	code: u32,
+	message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DnsRecord {
+	id: String,
+	name: String,
+	#[serde(rename = "type")]
+	record_type: String,
+	content: String,
+}
+
+#[derive(Debug, Serialize)]
+struct NewDnsRecord<'a> {
+	#[serde(rename = "type")]
+	record_type: &'a str,
+	name: &'a str,
+	content: String,
+	/// 1 means automatic.
+	ttl: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct DnsRecordPatch {
+	content: String,
+}
+
+impl Cloudflare {
+	pub fn new(client: Client, zone_id: String, token: String, name: String) -> Cloudflare {
+		Cloudflare {
+			client,
+			base_url: Url::parse("https://api.cloudflare.com/client/v4/")
+				.expect("supplied base URL should be valid"),
+			zone_id,
+			token,
+			name,
+		}
+	}
+
+	fn records_url(&self) -> Url {
+		let zone_id = &self.zone_id;
+		self.base_url
+			.join(&format!("zones/{zone_id}/dns_records"))
+			.expect("zone id should be a valid path segment")
+	}
+
+	async fn send<T: DeserializeOwned>(&self, req: RequestBuilder) -> Result<T, DnsProviderError> {
+		let res = req.bearer_auth(&self.token).send().await?;
+		let status = res.status();
+
+		parse_envelope(&res.text().await?).map_err(|err| match err {
+			DnsProviderError::Malformed(_) if !status.is_success() => {
+				DnsProviderError::Status(status.as_u16())
+			}
+			err => err,
+		})
+	}
+}
+
+#[async_trait]
+impl DnsProvider for Cloudflare {
+	async fn update(&self, ip: IpAddr) -> Result<String, DnsProviderError> {
+		let record_type = record_type(ip);
+
+		let records_url = self.records_url();
+		let existing: Vec<DnsRecord> = self
+			.send(
+				self.client
+					.get(records_url.clone())
+					.query(&[("type", record_type), ("name", &self.name)]),
+			)
+			.await?;
+
+		let record: DnsRecord = match existing.first() {
+			Some(record) => {
+				let mut url = records_url;
+				url.path_segments_mut()
+					.expect("base URL has path")
+					.push(&record.id);
+
+				self.send(self.client.patch(url).json(&DnsRecordPatch {
+					content: ip.to_string(),
+				}))
+				.await?
+			}
+			None => {
+				self.send(self.client.post(records_url).json(&NewDnsRecord {
+					record_type,
+					name: &self.name,
+					content: ip.to_string(),
+					ttl: 1,
+				}))
+				.await?
+			}
+		};
+
+		Ok(format!(
+			"{} {} record {} points to {}",
+			record.name, record.record_type, record.id, record.content
+		))
+	}
+
+	fn describe_update(&self, ip: IpAddr) -> String {
+		let record_type = record_type(ip);
+		let records_url = self.records_url();
+
+		format!(
+			"PATCH the {record_type} record of {} listed at {records_url}, or POST one if there is none, pointing it to {ip}",
+			self.name
+		)
+	}
+}
+
+fn record_type(ip: IpAddr) -> &'static str {
+	match ip {
+		IpAddr::V4(_) => "A",
+		IpAddr::V6(_) => "AAAA",
+	}
+}
+
+/// Unwraps the result from the envelope all API responses share, even failed ones.
+fn parse_envelope<T: DeserializeOwned>(body: &str) -> Result<T, DnsProviderError> {
+	let envelope = serde_json::from_str::<Envelope<T>>(body)
+		.map_err(|err| DnsProviderError::Malformed(err.to_string()))?;
+
+	if !envelope.success {
+		let errors = envelope
+			.errors
+			.iter()
+			.map(|ApiMessage { code, message }| format!("[{code}] {message}"))
+			.collect::<Vec<_>>();
+		return Err(DnsProviderError::Rejected(errors.join(", ")));
+	}
+
+	envelope
+		.result
+		.ok_or_else(|| DnsProviderError::Malformed("result missing from response".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_record_list() {
+		// Arrange
+		let body = include_str!("../../fixtures/cloudflare_list.json");
+
+		// Act
+		let records = parse_envelope::<Vec<DnsRecord>>(body).unwrap();
+
+		// Assert
+		assert_eq!(records.len(), 1);
+		assert_eq!(records[0].id, "372e67954025e0ba6aaa6d586b9e0b59");
+		assert_eq!(records[0].content, "198.51.100.4");
+	}
+
+	#[test]
+	fn parses_updated_record() {
+		// Arrange
+		let body = include_str!("../../fixtures/cloudflare_update.json");
+
+		// Act
+		let record = parse_envelope::<DnsRecord>(body).unwrap();
+
+		// Assert
+		assert_eq!(record.name, "home.example.com");
+		assert_eq!(record.record_type, "A");
+		assert_eq!(record.content, "203.0.113.7");
+	}
+
+	#[test]
+	fn parses_errors() {
+		// Arrange
+		let body = include_str!("../../fixtures/cloudflare_error.json");
+
+		// Act
+		let res = parse_envelope::<DnsRecord>(body);
+
+		// Assert
+		let Err(DnsProviderError::Rejected(msg)) = res else {
+			panic!("expected rejection, got {res:?}");
+		};
+		assert_eq!(msg, "[10000] Authentication error");
+	}
+}
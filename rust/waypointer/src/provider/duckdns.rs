@@ -0,0 +1,130 @@
+use crate::provider::{DnsProvider, DnsProviderError};
+use async_trait::async_trait;
+use reqwest::Client;
+use reqwest::{StatusCode, Url};
+use std::net::IpAddr;
+
+/// DuckDNS, updating the `domain` subdomain of `duckdns.org`.
+pub struct DuckDns {
+	client: Client,
+	domain: String,
+	token: String,
+}
+
+impl DuckDns {
+	pub fn new(client: Client, domain: String, token: String) -> DuckDns {
+		DuckDns {
+			client,
+			domain,
+			token,
+		}
+	}
+
+	fn url(&self, ip: IpAddr, token: &str) -> Url {
+		let mut url = Url::parse("https://www.duckdns.org/update")
+			.expect("supplied base URL should be valid");
+
+		let ip_param = match ip {
+			IpAddr::V4(_) => "ip",
+			IpAddr::V6(_) => "ipv6",
+		};
+		url.query_pairs_mut()
+			.append_pair("domains", &self.domain)
+			.append_pair("token", token)
+			.append_pair(ip_param, &ip.to_string());
+
+		url
+	}
+}
+
+#[async_trait]
+impl DnsProvider for DuckDns {
+	async fn update(&self, ip: IpAddr) -> Result<String, DnsProviderError> {
+		// the URL carries the token, so it must not end up in errors
+		let res = self
+			.client
+			.get(self.url(ip, &self.token))
+			.send()
+			.await
+			.map_err(reqwest::Error::without_url)?;
+
+		if res.status() != StatusCode::OK {
+			return Err(DnsProviderError::Status(res.status().as_u16()));
+		}
+
+		parse_response(&res.text().await.map_err(reqwest::Error::without_url)?)
+	}
+
+	fn describe_update(&self, ip: IpAddr) -> String {
+		format!("GET {}", self.url(ip, "REDACTED"))
+	}
+}
+
+/// DuckDNS answers with a bare `OK`, or `KO` without further detail.
+fn parse_response(body: &str) -> Result<String, DnsProviderError> {
+	match body.trim() {
+		"OK" => Ok("OK".to_string()),
+		"KO" => Err(DnsProviderError::Rejected(
+			"KO, the domain or token is invalid".to_string(),
+		)),
+		other => Err(DnsProviderError::Malformed(format!(
+			"expected OK or KO, got {other:?}"
+		))),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::provider::unreachable_client;
+	use error::ErrorExt;
+
+	#[test]
+	fn parses_responses() {
+		// Arrange
+		let ok = include_str!("../../fixtures/duckdns_ok.txt");
+		let ko = include_str!("../../fixtures/duckdns_ko.txt");
+
+		// Act
+		let ok = parse_response(ok);
+		let ko = parse_response(ko);
+
+		// Assert
+		assert_eq!(ok.unwrap(), "OK");
+		assert!(matches!(ko, Err(DnsProviderError::Rejected(_))));
+	}
+
+	#[test]
+	fn passes_ipv6_separately() {
+		// Arrange
+		let duckdns = DuckDns::new(Client::new(), "home".to_string(), "token".to_string());
+
+		// Act
+		let url = duckdns.url("2001:db8::7".parse().unwrap(), "token");
+
+		// Assert
+		assert_eq!(
+			url.query(),
+			Some("domains=home&token=token&ipv6=2001%3Adb8%3A%3A7")
+		);
+	}
+
+	#[tokio::test]
+	async fn errors_do_not_carry_the_url() {
+		// Arrange
+		let provider = DuckDns::new(
+			unreachable_client(),
+			"home".to_string(),
+			"secret-token".to_string(),
+		);
+
+		// Act
+		let res = provider.update("203.0.113.7".parse().unwrap()).await;
+
+		// Assert
+		let err = res.unwrap_err();
+		assert!(matches!(err, DnsProviderError::Request(_)));
+		let err = format!("{} {err:?}", err.to_pretty_string());
+		assert!(!err.contains("secret-token"), "{err}");
+	}
+}
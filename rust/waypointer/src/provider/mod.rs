@@ -0,0 +1,90 @@
+mod cloudflare;
+mod duckdns;
+mod namecheap;
+mod rfc2136;
+
+pub use cloudflare::*;
+pub use duckdns::*;
+pub use namecheap::*;
+pub use rfc2136::*;
+
+use async_trait::async_trait;
+use std::net::IpAddr;
+use thiserror::Error;
+
+/// Points a DNS record at an address.
+#[async_trait]
+pub trait DnsProvider: Send + Sync {
+	/// Gives a description of the outcome, as reported by the provider.
+	async fn update(&self, ip: IpAddr) -> Result<String, DnsProviderError>;
+
+	/// Describes what [`DnsProvider::update`] would send, with secrets redacted.
+	fn describe_update(&self, ip: IpAddr) -> String;
+}
+
+#[derive(Debug, Error)]
+pub enum DnsProviderError {
+	#[error("request to DNS provider failed")]
+	Request(#[from] reqwest::Error),
+	#[error("could not exchange messages with DNS server")]
+	Io(#[from] std::io::Error),
+	#[error("DNS provider responded with status {0}")]
+	Status(u16),
+	#[error("DNS provider rejected the update: {0}")]
+	Rejected(String),
+	#[error("DNS provider responded with malformed response: {0}")]
+	Malformed(String),
+}
+
+/// The fully qualified name of `host` within `domain`, `@` denoting the domain itself.
+pub fn fqdn(host: &str, domain: &str) -> String {
+	match host {
+		"@" => domain.to_string(),
+		host => format!("{host}.{domain}"),
+	}
+}
+
+/// Stands in for a provider in tests, recording updates and rejecting them while `reject` is set.
+/// Clones share both.
+#[cfg(test)]
+#[derive(Default, Clone)]
+pub struct FakeDnsProvider {
+	pub updates: std::sync::Arc<std::sync::Mutex<Vec<IpAddr>>>,
+	pub reject: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+#[cfg(test)]
+impl FakeDnsProvider {
+	pub fn updates(&self) -> Vec<IpAddr> {
+		self.updates.lock().unwrap().clone()
+	}
+
+	pub fn set_reject(&self, reject: bool) {
+		self.reject
+			.store(reject, std::sync::atomic::Ordering::Relaxed);
+	}
+}
+
+#[cfg(test)]
+#[async_trait]
+impl DnsProvider for FakeDnsProvider {
+	async fn update(&self, ip: IpAddr) -> Result<String, DnsProviderError> {
+		if self.reject.load(std::sync::atomic::Ordering::Relaxed) {
+			return Err(DnsProviderError::Rejected("fake rejection".to_string()));
+		}
+
+		self.updates.lock().unwrap().push(ip);
+		Ok(format!("published {ip}"))
+	}
+
+	fn describe_update(&self, ip: IpAddr) -> String {
+		format!("publish {ip}")
+	}
+}
+
+/// A client failing every request, by sending it through a proxy nothing listens on.
+#[cfg(test)]
+fn unreachable_client() -> reqwest::Client {
+	let proxy = reqwest::Proxy::all("http://127.0.0.1:1").unwrap();
+	reqwest::Client::builder().proxy(proxy).build().unwrap()
+}
@@ -0,0 +1,192 @@
+use crate::provider::{DnsProvider, DnsProviderError};
+use async_trait::async_trait;
+use reqwest::Client;
+use reqwest::{StatusCode, Url};
+use roxmltree::{Document, Node};
+use std::net::IpAddr;
+
+/// Namecheap's dynamic DNS, authenticated by the per-domain dynamic DNS password.
+pub struct Namecheap {
+	client: Client,
+	host: String,
+	domain: String,
+	password: String,
+}
+
+impl Namecheap {
+	pub fn new(client: Client, host: String, domain: String, password: String) -> Namecheap {
+		Namecheap {
+			client,
+			host,
+			domain,
+			password,
+		}
+	}
+
+	fn url(&self, ip: IpAddr, password: &str) -> Url {
+		let mut url = Url::parse("https://dynamicdns.park-your-domain.com/update")
+			.expect("supplied base URL should be valid");
+
+		url.query_pairs_mut()
+			.append_pair("host", &self.host)
+			.append_pair("domain", &self.domain)
+			.append_pair("password", password)
+			.append_pair("ip", &ip.to_string());
+
+		url
+	}
+}
+
+#[async_trait]
+impl DnsProvider for Namecheap {
+	async fn update(&self, ip: IpAddr) -> Result<String, DnsProviderError> {
+		// the URL carries the password, so it must not end up in errors
+		let res = self
+			.client
+			.get(self.url(ip, &self.password))
+			.send()
+			.await
+			.map_err(reqwest::Error::without_url)?;
+
+		if res.status() != StatusCode::OK {
+			return Err(DnsProviderError::Status(res.status().as_u16()));
+		}
+
+		parse_response(&res.text().await.map_err(reqwest::Error::without_url)?)
+	}
+
+	fn describe_update(&self, ip: IpAddr) -> String {
+		format!("GET {}", self.url(ip, "REDACTED"))
+	}
+}
+
+/// Parses the `interface-response` document, which lists errors and responses as child elements.
+fn parse_response(body: &str) -> Result<String, DnsProviderError> {
+	let malformed = |msg: &str| DnsProviderError::Malformed(msg.to_string());
+
+	let document = Document::parse(body).map_err(|err| malformed(&err.to_string()))?;
+
+	let root = document
+		.descendants()
+		.find(|n| n.has_tag_name("interface-response"))
+		.ok_or_else(|| malformed("root node missing"))?;
+	let errors = child(root, "errors")
+		.ok_or_else(|| malformed("errors missing from response"))?
+		.children()
+		.filter(Node::is_element)
+		.map(|error| {
+			error
+				.text()
+				.map(|t| t.to_string())
+				.ok_or_else(|| malformed("text missing from error"))
+		})
+		.collect::<Result<Vec<_>, _>>()?;
+	let responses = child(root, "responses")
+		.ok_or_else(|| malformed("responses missing from response"))?
+		.children()
+		.filter(Node::is_element)
+		.map(|response| {
+			let description = child_text(response, "Description")
+				.ok_or_else(|| malformed("response missing description"))?;
+			let number = child_text(response, "ResponseNumber")
+				.ok_or_else(|| malformed("response missing number"))?;
+
+			Ok(format!("[{number}] {description}"))
+		})
+		.collect::<Result<Vec<_>, DnsProviderError>>()?;
+
+	if !errors.is_empty() {
+		return Err(DnsProviderError::Rejected(format!(
+			"errors: {}, responses: {}",
+			errors.join(", "),
+			responses.join(", ")
+		)));
+	}
+
+	Ok(format!("responses: {responses:?}"))
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+	node.children().find(|n| n.has_tag_name(tag))
+}
+
+fn child_text<'a>(node: Node<'a, '_>, tag: &str) -> Option<&'a str> {
+	child(node, tag).and_then(|n| n.text())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::provider::unreachable_client;
+	use error::ErrorExt;
+
+	#[test]
+	fn parses_success() {
+		// Arrange
+		let body = include_str!("../../fixtures/namecheap_success.xml");
+
+		// Act
+		let res = parse_response(body);
+
+		// Assert
+		assert_eq!(res.unwrap(), "responses: []");
+	}
+
+	#[test]
+	fn redacts_password_from_description() {
+		// Arrange
+		let namecheap = Namecheap::new(
+			Client::new(),
+			"@".to_string(),
+			"example.com".to_string(),
+			"secret-password".to_string(),
+		);
+
+		// Act
+		let description = namecheap.describe_update("203.0.113.7".parse().unwrap());
+
+		// Assert
+		assert_eq!(
+			description,
+			"GET https://dynamicdns.park-your-domain.com/update?host=%40&domain=example.com&password=REDACTED&ip=203.0.113.7"
+		);
+	}
+
+	#[test]
+	fn parses_errors() {
+		// Arrange
+		let body = include_str!("../../fixtures/namecheap_error.xml");
+
+		// Act
+		let res = parse_response(body);
+
+		// Assert
+		let Err(DnsProviderError::Rejected(msg)) = res else {
+			panic!("expected rejection, got {res:?}");
+		};
+		assert_eq!(
+			msg,
+			"errors: Passwords do not match, responses: [304156] Parameter Password is invalid"
+		);
+	}
+
+	#[tokio::test]
+	async fn errors_do_not_carry_the_url() {
+		// Arrange
+		let provider = Namecheap::new(
+			unreachable_client(),
+			"@".to_string(),
+			"example.com".to_string(),
+			"secret-password".to_string(),
+		);
+
+		// Act
+		let res = provider.update("203.0.113.7".parse().unwrap()).await;
+
+		// Assert
+		let err = res.unwrap_err();
+		assert!(matches!(err, DnsProviderError::Request(_)));
+		let err = format!("{} {err:?}", err.to_pretty_string());
+		assert!(!err.contains("secret-password"), "{err}");
+	}
+}
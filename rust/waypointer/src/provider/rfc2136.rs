@@ -0,0 +1,384 @@
+use crate::provider::{DnsProvider, DnsProviderError};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::io::ErrorKind;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+use tokio::net::UdpSocket;
+
+const OPCODE_UPDATE: u16 = 5;
+const TYPE_A: u16 = 1;
+const TYPE_SOA: u16 = 6;
+const TYPE_AAAA: u16 = 28;
+const TYPE_TSIG: u16 = 250;
+const CLASS_IN: u16 = 1;
+const CLASS_ANY: u16 = 255;
+const TSIG_ALGORITHM: &str = "hmac-sha256";
+const TSIG_FUDGE: u16 = 300;
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Dynamic updates as of RFC 2136, sent over UDP to the primary server of the zone and signed with
+/// TSIG (RFC 8945) if a key is given. Replaces all records of the type matching the address.
+pub struct Rfc2136 {
+	server: SocketAddr,
+	zone_name: String,
+	zone: Vec<u8>,
+	record_name: String,
+	name: Vec<u8>,
+	ttl: u32,
+	key: Option<TsigKey>,
+}
+
+/// A key shared with the DNS server, for `hmac-sha256` only.
+pub struct TsigKey {
+	name: Vec<u8>,
+	secret: Vec<u8>,
+}
+
+#[derive(Debug, Error)]
+#[error("invalid DNS name {0:?}")]
+pub struct InvalidName(String);
+
+impl Rfc2136 {
+	pub fn new(
+		server: SocketAddr,
+		zone: &str,
+		name: &str,
+		ttl: u32,
+		key: Option<TsigKey>,
+	) -> Result<Rfc2136, InvalidName> {
+		Ok(Rfc2136 {
+			server,
+			zone_name: zone.to_string(),
+			zone: encode_name(zone)?,
+			record_name: name.to_string(),
+			name: encode_name(name)?,
+			ttl,
+			key,
+		})
+	}
+}
+
+impl TsigKey {
+	pub fn new(name: &str, secret: Vec<u8>) -> Result<TsigKey, InvalidName> {
+		Ok(TsigKey {
+			name: encode_name(&name.to_ascii_lowercase())?,
+			secret,
+		})
+	}
+}
+
+#[async_trait]
+impl DnsProvider for Rfc2136 {
+	async fn update(&self, ip: IpAddr) -> Result<String, DnsProviderError> {
+		let id = fastrand::u16(..);
+		let mut msg = update_message(id, &self.zone, &self.name, self.ttl, ip);
+		if let Some(key) = &self.key {
+			sign(&mut msg, key, unix_time());
+		}
+
+		let local_addr = match self.server {
+			SocketAddr::V4(_) => "0.0.0.0:0",
+			SocketAddr::V6(_) => "[::]:0",
+		};
+		let socket = UdpSocket::bind(local_addr).await?;
+		socket.connect(self.server).await?;
+		socket.send(&msg).await?;
+
+		let mut buf = [0; 512];
+		let len = tokio::time::timeout(TIMEOUT, socket.recv(&mut buf))
+			.await
+			.map_err(|_| std::io::Error::from(ErrorKind::TimedOut))??;
+
+		parse_response(id, &buf[..len])
+	}
+
+	fn describe_update(&self, ip: IpAddr) -> String {
+		let record_type = match ip {
+			IpAddr::V4(_) => "A",
+			IpAddr::V6(_) => "AAAA",
+		};
+		let signed = match self.key {
+			Some(_) => "signed",
+			None => "unsigned",
+		};
+
+		format!(
+			"{signed} UPDATE of zone {} to {}, replacing the {record_type} records of {} with {ip}, TTL {}",
+			self.zone_name, self.server, self.record_name, self.ttl
+		)
+	}
+}
+
+/// Deletes the records of the type matching `ip` at `name` and adds one pointing at `ip`.
+fn update_message(id: u16, zone: &[u8], name: &[u8], ttl: u32, ip: IpAddr) -> Vec<u8> {
+	let (record_type, rdata) = match ip {
+		IpAddr::V4(ip) => (TYPE_A, ip.octets().to_vec()),
+		IpAddr::V6(ip) => (TYPE_AAAA, ip.octets().to_vec()),
+	};
+
+	let mut msg = Vec::with_capacity(512);
+	// header: id, flags, zone count, prerequisite count, update count, additional count
+	for field in [id, OPCODE_UPDATE << 11, 1, 0, 2, 0] {
+		msg.extend(field.to_be_bytes());
+	}
+
+	msg.extend(zone);
+	msg.extend(TYPE_SOA.to_be_bytes());
+	msg.extend(CLASS_IN.to_be_bytes());
+
+	// deleting an RRset is denoted by class ANY, TTL 0 and no data
+	msg.extend(name);
+	msg.extend(record_type.to_be_bytes());
+	msg.extend(CLASS_ANY.to_be_bytes());
+	msg.extend(0u32.to_be_bytes());
+	msg.extend(0u16.to_be_bytes());
+
+	msg.extend(name);
+	msg.extend(record_type.to_be_bytes());
+	msg.extend(CLASS_IN.to_be_bytes());
+	msg.extend(ttl.to_be_bytes());
+	msg.extend((rdata.len() as u16).to_be_bytes());
+	msg.extend(rdata);
+
+	msg
+}
+
+/// Appends a TSIG record, its MAC covering the message and the TSIG variables.
+fn sign(msg: &mut Vec<u8>, key: &TsigKey, time_signed: u64) {
+	let algorithm = encode_name(TSIG_ALGORITHM).expect("algorithm is a valid name");
+	let time_signed = &time_signed.to_be_bytes()[2..];
+
+	let mut mac = Hmac::<Sha256>::new_from_slice(&key.secret).expect("HMAC takes keys of any size");
+	mac.update(msg);
+	mac.update(&key.name);
+	mac.update(&CLASS_ANY.to_be_bytes());
+	mac.update(&0u32.to_be_bytes());
+	mac.update(&algorithm);
+	mac.update(time_signed);
+	mac.update(&TSIG_FUDGE.to_be_bytes());
+	// error and other length
+	mac.update(&[0; 4]);
+	let mac = mac.finalize().into_bytes();
+
+	let mut rdata = algorithm;
+	rdata.extend(time_signed);
+	rdata.extend(TSIG_FUDGE.to_be_bytes());
+	rdata.extend((mac.len() as u16).to_be_bytes());
+	rdata.extend(mac);
+	// original id, error and other length
+	rdata.extend(&msg[..2]);
+	rdata.extend([0; 4]);
+
+	msg.extend(&key.name);
+	msg.extend(TYPE_TSIG.to_be_bytes());
+	msg.extend(CLASS_ANY.to_be_bytes());
+	msg.extend(0u32.to_be_bytes());
+	msg.extend((rdata.len() as u16).to_be_bytes());
+	msg.extend(rdata);
+
+	let additional_count = u16::from_be_bytes([msg[10], msg[11]]) + 1;
+	msg[10..12].copy_from_slice(&additional_count.to_be_bytes());
+}
+
+/// Checks the response code of the response to the message `id`. Its TSIG record, if any, is not
+/// verified, so a forged success is not told apart.
+fn parse_response(id: u16, response: &[u8]) -> Result<String, DnsProviderError> {
+	let malformed = |msg: &str| DnsProviderError::Malformed(msg.to_string());
+
+	let header = response
+		.get(..4)
+		.ok_or_else(|| malformed("response shorter than header"))?;
+	let response_id = u16::from_be_bytes([header[0], header[1]]);
+	let flags = u16::from_be_bytes([header[2], header[3]]);
+
+	if response_id != id {
+		return Err(malformed(&format!(
+			"response id {response_id} does not match request id {id}"
+		)));
+	}
+	if flags & 0x8000 == 0 || (flags >> 11) & 0xF != OPCODE_UPDATE {
+		return Err(malformed("not a response to an update"));
+	}
+
+	match flags & 0xF {
+		0 => Ok("NOERROR".to_string()),
+		1 => Err(DnsProviderError::Rejected("FORMERR".to_string())),
+		2 => Err(DnsProviderError::Rejected("SERVFAIL".to_string())),
+		3 => Err(DnsProviderError::Rejected("NXDOMAIN".to_string())),
+		4 => Err(DnsProviderError::Rejected("NOTIMP".to_string())),
+		5 => Err(DnsProviderError::Rejected("REFUSED".to_string())),
+		6 => Err(DnsProviderError::Rejected("YXDOMAIN".to_string())),
+		7 => Err(DnsProviderError::Rejected("YXRRSET".to_string())),
+		8 => Err(DnsProviderError::Rejected("NXRRSET".to_string())),
+		9 => Err(DnsProviderError::Rejected("NOTAUTH".to_string())),
+		10 => Err(DnsProviderError::Rejected("NOTZONE".to_string())),
+		rcode => Err(DnsProviderError::Rejected(format!("RCODE {rcode}"))),
+	}
+}
+
+/// Encodes `name` as a sequence of length prefixed labels, ending with the empty root label.
+fn encode_name(name: &str) -> Result<Vec<u8>, InvalidName> {
+	let invalid = || InvalidName(name.to_string());
+
+	let mut encoded = Vec::new();
+	for label in name.trim_end_matches('.').split('.') {
+		if label.is_empty() || label.len() > 63 {
+			return Err(invalid());
+		}
+
+		encoded.push(label.len() as u8);
+		encoded.extend(label.as_bytes());
+	}
+	encoded.push(0);
+
+	if encoded.len() > 255 {
+		return Err(invalid());
+	}
+
+	Ok(encoded)
+}
+
+fn unix_time() -> u64 {
+	SystemTime::now()
+		.duration_since(SystemTime::UNIX_EPOCH)
+		.expect("clock is after the epoch")
+		.as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_response_codes() {
+		// Arrange
+		let noerror_response = include_bytes!("../../fixtures/rfc2136_noerror.bin");
+		let notauth_response = include_bytes!("../../fixtures/rfc2136_notauth.bin");
+
+		// Act
+		let noerror = parse_response(0x1234, noerror_response);
+		let notauth = parse_response(0x1234, notauth_response);
+		let mismatched = parse_response(0x4321, noerror_response);
+
+		// Assert
+		assert_eq!(noerror.unwrap(), "NOERROR");
+		assert!(matches!(notauth, Err(DnsProviderError::Rejected(rcode)) if rcode == "NOTAUTH"));
+		assert!(matches!(mismatched, Err(DnsProviderError::Malformed(_))));
+	}
+
+	/// The end of the name starting at `pos`, stopping at a compression pointer rather than following it.
+	fn skip_name(msg: &[u8], mut pos: usize) -> usize {
+		loop {
+			match msg[pos] {
+				0 => return pos + 1,
+				len if len & 0xC0 == 0xC0 => return pos + 2,
+				len => pos += 1 + len as usize,
+			}
+		}
+	}
+
+	fn read_u16(msg: &[u8], pos: usize) -> u16 {
+		u16::from_be_bytes([msg[pos], msg[pos + 1]])
+	}
+
+	/// Verifies the trailing TSIG record of `msg` the way a receiving server does, as described in
+	/// RFC 8945 section 4.3, by stripping it and recomputing the MAC from the fields it carries.
+	fn verify(msg: &[u8], secret: &[u8]) -> Result<(), String> {
+		let section_counts = [4, 6, 8, 10].map(|pos| read_u16(msg, pos) as usize);
+		let [zone_count, prerequisite_count, update_count, additional_count] = section_counts;
+		let mut pos = 12;
+		for _ in 0..zone_count {
+			pos = skip_name(msg, pos) + 4;
+		}
+		for _ in 0..prerequisite_count + update_count + additional_count - 1 {
+			let rdata = skip_name(msg, pos) + 10;
+			pos = rdata + read_u16(msg, rdata - 2) as usize;
+		}
+
+		let tsig_start = pos;
+		let key_name = &msg[pos..skip_name(msg, pos)];
+		pos += key_name.len();
+		let class_and_ttl = &msg[pos + 2..pos + 8];
+		if read_u16(msg, pos) != TYPE_TSIG || class_and_ttl != [0, 255, 0, 0, 0, 0] {
+			return Err("last record is not a TSIG record".to_string());
+		}
+		let rdata = &msg[pos + 10..];
+		if rdata.len() != read_u16(msg, pos + 8) as usize {
+			return Err("TSIG record is not the last".to_string());
+		}
+
+		let algorithm = &rdata[..skip_name(rdata, 0)];
+		let time_and_fudge = &rdata[algorithm.len()..algorithm.len() + 8];
+		let mac_size = read_u16(rdata, algorithm.len() + 8) as usize;
+		let mac = &rdata[algorithm.len() + 10..algorithm.len() + 10 + mac_size];
+		let original_id = &rdata[algorithm.len() + 10 + mac_size..][..2];
+		let error_and_other = &rdata[algorithm.len() + 12 + mac_size..];
+
+		let mut unsigned = msg[..tsig_start].to_vec();
+		unsigned[..2].copy_from_slice(original_id);
+		unsigned[10..12].copy_from_slice(&(additional_count as u16 - 1).to_be_bytes());
+
+		let mut expected = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+		expected.update(&unsigned);
+		expected.update(key_name);
+		expected.update(class_and_ttl);
+		expected.update(algorithm);
+		expected.update(time_and_fudge);
+		expected.update(error_and_other);
+		expected.verify_slice(mac).map_err(|err| err.to_string())
+	}
+
+	#[test]
+	fn signs_update() {
+		// Arrange
+		let zone = encode_name("example.com").unwrap();
+		let name = encode_name("home.example.com").unwrap();
+		let key = TsigKey::new("Waypointer.", b"secret-key-bytes".to_vec()).unwrap();
+		let mut msg = update_message(0x1234, &zone, &name, 300, "203.0.113.7".parse().unwrap());
+
+		// Act
+		sign(&mut msg, &key, 1709294400);
+
+		// Assert
+		assert_eq!(verify(&msg, b"secret-key-bytes"), Ok(()));
+		assert!(verify(&msg, b"other-key-bytes").is_err());
+		let tsig = &msg[msg.len() - 83..];
+		assert_eq!(&tsig[..12], b"\x0awaypointer\x00");
+		assert_eq!(&tsig[22..35], b"\x0bhmac-sha256\x00");
+		assert_eq!(tsig[35..41], 1709294400u64.to_be_bytes()[2..]);
+	}
+
+	#[test]
+	fn tampered_update_fails_verification() {
+		// Arrange
+		let zone = encode_name("example.com").unwrap();
+		let name = encode_name("home.example.com").unwrap();
+		let key = TsigKey::new("waypointer", b"secret-key-bytes".to_vec()).unwrap();
+		let mut msg = update_message(0x1234, &zone, &name, 300, "203.0.113.7".parse().unwrap());
+		sign(&mut msg, &key, 1709294400);
+
+		// Act
+		let ip = msg.len() - 83 - 1;
+		msg[ip] = 8;
+
+		// Assert
+		assert!(verify(&msg, b"secret-key-bytes").is_err());
+	}
+
+	#[test]
+	fn rejects_invalid_names() {
+		// Arrange
+		let long_label = "a".repeat(64);
+
+		// Act
+		let empty_label = encode_name("home..example.com");
+		let long_label = encode_name(&long_label);
+
+		// Assert
+		assert!(empty_label.is_err());
+		assert!(long_label.is_err());
+	}
+}
@@ -0,0 +1,264 @@
+use crate::provider::{
+	fqdn, Cloudflare, DnsProvider, DuckDns, InvalidName, Namecheap, Rfc2136, TsigKey,
+};
+use crate::sync::{Family, Record};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use config::FromArg;
+use reqwest::Client;
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The records to keep updated, parsed from a JSON array of [`RecordConfig`]s.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Records(pub Vec<RecordConfig>);
+
+/// A record to keep updated, as listed in the records file. Secrets are referenced by path, so
+/// that the file itself can be shared freely.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RecordConfig {
+	/// Whether to publish the IPv4 address as A record.
+	#[serde(default = "default_true")]
+	pub ipv4: bool,
+	/// Whether to publish the IPv6 address as AAAA record.
+	#[serde(default)]
+	pub ipv6: bool,
+	#[serde(flatten)]
+	pub provider: ProviderConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "provider", rename_all = "lowercase")]
+pub enum ProviderConfig {
+	Namecheap {
+		host: String,
+		domain: String,
+		/// The dynamic DNS password of the domain.
+		password_file: PathBuf,
+	},
+	Cloudflare {
+		zone_id: String,
+		host: String,
+		domain: String,
+		/// An API token allowed to edit DNS records of the zone.
+		token_file: PathBuf,
+	},
+	#[serde(rename = "duckdns")]
+	DuckDns {
+		/// The subdomain of `duckdns.org`.
+		domain: String,
+		token_file: PathBuf,
+	},
+	Rfc2136 {
+		server: SocketAddr,
+		host: String,
+		/// The zone containing the record, i.e. the domain.
+		domain: String,
+		#[serde(default = "default_ttl")]
+		ttl: u32,
+		tsig_key_name: Option<String>,
+		/// The base64 encoded TSIG key, required if `tsig_key_name` is set.
+		tsig_key_file: Option<PathBuf>,
+	},
+}
+
+#[derive(Debug, Error)]
+pub enum RecordError {
+	#[error("could not read secret file {0:?}")]
+	Secret(PathBuf, #[source] std::io::Error),
+	#[error("TSIG key in {0:?} is not valid base64")]
+	InvalidTsigKey(PathBuf, #[source] base64::DecodeError),
+	#[error("TSIG key name is set but no TSIG key file")]
+	MissingTsigKey,
+	#[error(transparent)]
+	InvalidName(#[from] InvalidName),
+	#[error("{0} publishes neither IPv4 nor IPv6 addresses")]
+	NoFamily(String),
+	#[error("{0} can only publish IPv4 addresses")]
+	Ipv6Unsupported(String),
+}
+
+impl FromArg for Records {
+	type Error = serde_json::Error;
+
+	fn parse_arg(argument: &str) -> Result<Self, Self::Error> {
+		serde_json::from_str(argument)
+	}
+}
+
+impl RecordConfig {
+	/// Reads the secrets and sets up the provider.
+	pub fn build(&self, client: &Client) -> Result<Record, RecordError> {
+		let name = self.name();
+
+		let families = [(self.ipv4, Family::V4), (self.ipv6, Family::V6)]
+			.into_iter()
+			.filter_map(|(enabled, family)| enabled.then_some(family))
+			.collect::<Vec<_>>();
+		if families.is_empty() {
+			return Err(RecordError::NoFamily(name));
+		}
+
+		let provider: Box<dyn DnsProvider> = match &self.provider {
+			ProviderConfig::Namecheap {
+				host,
+				domain,
+				password_file,
+			} => {
+				// Namecheap's dynamic DNS only ever updates A records.
+				if self.ipv6 {
+					return Err(RecordError::Ipv6Unsupported(name));
+				}
+
+				Box::new(Namecheap::new(
+					client.clone(),
+					host.clone(),
+					domain.clone(),
+					read_secret(password_file)?,
+				))
+			}
+			ProviderConfig::Cloudflare {
+				zone_id,
+				host,
+				domain,
+				token_file,
+			} => Box::new(Cloudflare::new(
+				client.clone(),
+				zone_id.clone(),
+				read_secret(token_file)?,
+				fqdn(host, domain),
+			)),
+			ProviderConfig::DuckDns { domain, token_file } => Box::new(DuckDns::new(
+				client.clone(),
+				domain.clone(),
+				read_secret(token_file)?,
+			)),
+			ProviderConfig::Rfc2136 {
+				server,
+				host,
+				domain,
+				ttl,
+				tsig_key_name,
+				tsig_key_file,
+			} => {
+				let key = match (tsig_key_name, tsig_key_file) {
+					(Some(key_name), Some(key_file)) => {
+						let secret = BASE64_STANDARD
+							.decode(read_secret(key_file)?)
+							.map_err(|err| RecordError::InvalidTsigKey(key_file.clone(), err))?;
+						Some(TsigKey::new(key_name, secret)?)
+					}
+					(Some(_), None) => return Err(RecordError::MissingTsigKey),
+					(None, _) => None,
+				};
+
+				Box::new(Rfc2136::new(
+					*server,
+					domain,
+					&fqdn(host, domain),
+					*ttl,
+					key,
+				)?)
+			}
+		};
+
+		Ok(Record {
+			name,
+			provider,
+			families,
+		})
+	}
+
+	/// Identifies the record in logs and the state file, e.g. `home.example.com (cloudflare)`.
+	pub fn name(&self) -> String {
+		let (fqdn, provider) = match &self.provider {
+			ProviderConfig::Namecheap { host, domain, .. } => (fqdn(host, domain), "namecheap"),
+			ProviderConfig::Cloudflare { host, domain, .. } => (fqdn(host, domain), "cloudflare"),
+			ProviderConfig::DuckDns { domain, .. } => (format!("{domain}.duckdns.org"), "duckdns"),
+			ProviderConfig::Rfc2136 { host, domain, .. } => (fqdn(host, domain), "rfc2136"),
+		};
+
+		format!("{fqdn} ({provider})")
+	}
+}
+
+fn read_secret(path: &Path) -> Result<String, RecordError> {
+	std::fs::read_to_string(path)
+		.map(|secret| secret.trim().to_string())
+		.map_err(|err| RecordError::Secret(path.to_path_buf(), err))
+}
+
+fn default_true() -> bool {
+	true
+}
+
+fn default_ttl() -> u32 {
+	300
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_records() {
+		// Arrange
+		let argument = include_str!("../fixtures/records.json");
+
+		// Act
+		let Records(records) = Records::parse_arg(argument).unwrap();
+
+		// Assert
+		let names = records.iter().map(RecordConfig::name).collect::<Vec<_>>();
+		assert_eq!(
+			names,
+			[
+				"example.com (namecheap)",
+				"www.example.com (namecheap)",
+				"home.example.org (cloudflare)",
+				"home.duckdns.org (duckdns)",
+				"home.example.net (rfc2136)",
+			]
+		);
+		assert_eq!(
+			records[4],
+			RecordConfig {
+				ipv4: false,
+				ipv6: true,
+				provider: ProviderConfig::Rfc2136 {
+					server: "192.0.2.53:53".parse().unwrap(),
+					host: "home".to_string(),
+					domain: "example.net".to_string(),
+					ttl: 300,
+					tsig_key_name: Some("waypointer".to_string()),
+					tsig_key_file: Some("/run/secrets/tsig_key".into()),
+				},
+			}
+		);
+	}
+
+	#[test]
+	fn rejects_ipv6_for_namecheap() {
+		// Arrange
+		let dir = tempfile::tempdir().unwrap();
+		let password_file = dir.path().join("password");
+		std::fs::write(&password_file, "password\n").unwrap();
+		let record = RecordConfig {
+			ipv4: true,
+			ipv6: true,
+			provider: ProviderConfig::Namecheap {
+				host: "@".to_string(),
+				domain: "example.com".to_string(),
+				password_file,
+			},
+		};
+
+		// Act
+		let res = record.build(&Client::new());
+
+		// Assert
+		assert!(matches!(res, Err(RecordError::Ipv6Unsupported(_))));
+	}
+}
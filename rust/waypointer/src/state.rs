@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// The address last published successfully.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Published {
+	pub ip: IpAddr,
+	pub at: DateTime<Utc>,
+}
+
+/// The address last published per record and address family, keyed by e.g.
+/// `home.example.com (cloudflare) AAAA`.
+pub type State = BTreeMap<String, Published>;
+
+/// Persists the [`State`] across restarts as JSON.
+#[derive(Debug, Clone)]
+pub struct StateFile {
+	path: PathBuf,
+}
+
+#[derive(Debug, Clone, Error)]
+pub enum StateError {
+	#[error("could not access state file {0:?}")]
+	Io(PathBuf, #[source] Arc<std::io::Error>),
+}
+
+impl StateFile {
+	pub fn new(path: impl Into<PathBuf>) -> StateFile {
+		StateFile { path: path.into() }
+	}
+
+	/// Gives an empty state if nothing was published yet, i.e. the file does not exist, or if the
+	/// file is malformed, e.g. written by an older version, so that everything is published again
+	/// and the file replaced.
+	pub fn load(&self) -> Result<State, StateError> {
+		let contents = match std::fs::read_to_string(&self.path) {
+			Ok(contents) => contents,
+			Err(err) if err.kind() == ErrorKind::NotFound => return Ok(State::new()),
+			Err(err) => return Err(StateError::Io(self.path.clone(), Arc::new(err))),
+		};
+
+		serde_json::from_str(&contents).or_else(|err| {
+			println!(
+				"{} | ignoring malformed state file {:?}, publishing every record again: {err}",
+				Utc::now(),
+				self.path
+			);
+			Ok(State::new())
+		})
+	}
+
+	/// Replaces the file atomically, so that a crash never leaves it half written.
+	pub fn save(&self, state: &State) -> Result<(), StateError> {
+		let contents = serde_json::to_string_pretty(state).expect("state is serializable");
+
+		let mut tmp_path = self.path.clone().into_os_string();
+		tmp_path.push(".tmp");
+
+		std::fs::write(&tmp_path, contents)
+			.and_then(|()| std::fs::rename(&tmp_path, &self.path))
+			.map_err(|err| StateError::Io(self.path.clone(), Arc::new(err)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use chrono::TimeZone;
+
+	#[test]
+	fn round_trips() {
+		// Arrange
+		let dir = tempfile::tempdir().unwrap();
+		let state = StateFile::new(dir.path().join("state.json"));
+		let published = State::from([(
+			"home.example.com (cloudflare) A".to_string(),
+			Published {
+				ip: "203.0.113.7".parse().unwrap(),
+				at: Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap(),
+			},
+		)]);
+
+		// Act
+		let before = state.load().unwrap();
+		state.save(&published).unwrap();
+		let after = state.load().unwrap();
+
+		// Assert
+		assert_eq!(before, State::new());
+		assert_eq!(after, published);
+	}
+
+	#[test]
+	fn malformed_is_empty() {
+		// Arrange
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("state.json");
+		let old_format = r#"{ "ip": "203.0.113.7", "at": "2024-03-01T12:00:00Z" }"#;
+		std::fs::write(&path, old_format).unwrap();
+		let state = StateFile::new(path);
+
+		// Act
+		let loaded = state.load();
+
+		// Assert
+		assert_eq!(loaded.unwrap(), State::new());
+	}
+}
@@ -0,0 +1,467 @@
+use crate::ip_lookup::{IpLookup, IpLookupError};
+use crate::provider::{DnsProvider, DnsProviderError};
+use crate::state::{Published, StateError, StateFile};
+use chrono::{DateTime, TimeDelta, Utc};
+use std::fmt::{Display, Formatter};
+use std::net::IpAddr;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Family {
+	V4,
+	V6,
+}
+
+/// A record kept updated by a provider, with the address families it publishes.
+pub struct Record {
+	/// Identifies the record in logs and the state file.
+	pub name: String,
+	pub provider: Box<dyn DnsProvider>,
+	pub families: Vec<Family>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+	/// The address did not change since it was last published, less than the max age ago.
+	Unchanged(IpAddr),
+	Published(IpAddr, String),
+	/// The address would have been published if not for a dry run, as described.
+	WouldPublish(IpAddr, String),
+}
+
+#[derive(Debug, Error)]
+pub enum SyncError {
+	#[error("could not look up public {0} address")]
+	Lookup(Family, #[source] Arc<IpLookupError>),
+	#[error("could not publish {0}")]
+	Publish(IpAddr, #[source] DnsProviderError),
+}
+
+/// The outcome of a sync which got to sync the records.
+#[derive(Debug)]
+pub struct SyncReport {
+	pub statuses: Vec<RecordStatus>,
+	/// Set if the state could not be saved, hence what was published will be published again.
+	pub save_error: Option<StateError>,
+}
+
+/// The outcome of syncing one address family of one record.
+#[derive(Debug)]
+pub struct RecordStatus {
+	pub record: String,
+	pub family: Family,
+	pub result: Result<Outcome, SyncError>,
+}
+
+impl Family {
+	pub fn of(ip: IpAddr) -> Family {
+		match ip {
+			IpAddr::V4(_) => Family::V4,
+			IpAddr::V6(_) => Family::V6,
+		}
+	}
+
+	pub fn record_type(self) -> &'static str {
+		match self {
+			Family::V4 => "A",
+			Family::V6 => "AAAA",
+		}
+	}
+}
+
+impl Display for Family {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Family::V4 => write!(f, "IPv4"),
+			Family::V6 => write!(f, "IPv6"),
+		}
+	}
+}
+
+/// Publishes the current addresses to the providers of `records`, each if it differs from the one
+/// published last for the record, or that one was published at least `max_age` ago. A dry run
+/// only describes what would be published, leaving providers and the state alone.
+///
+/// Addresses are looked up once per family, and only if some record publishes it. Failures are
+/// reported per record, except for the state, which is only saved after all records are synced.
+/// Failing to load it aborts the sync, failing to save it is reported along with the records.
+pub async fn sync(
+	lookup_v4: &dyn IpLookup,
+	lookup_v6: &dyn IpLookup,
+	records: &[Record],
+	state: &StateFile,
+	max_age: TimeDelta,
+	dry_run: bool,
+	now: DateTime<Utc>,
+) -> Result<SyncReport, StateError> {
+	let mut published = state.load()?;
+
+	let ip_v4 = lookup_if_published(records, lookup_v4, Family::V4).await;
+	let ip_v6 = lookup_if_published(records, lookup_v6, Family::V6).await;
+
+	let mut statuses = Vec::new();
+	let mut changed = false;
+	for record in records {
+		for &family in &record.families {
+			let ip = match family {
+				Family::V4 => &ip_v4,
+				Family::V6 => &ip_v6,
+			};
+			let ip = ip
+				.as_ref()
+				.expect("address is looked up if a record publishes it");
+			let key = format!("{} {}", record.name, family.record_type());
+
+			let result = match ip {
+				Ok(ip) => {
+					let ip = *ip;
+					let is_fresh = published
+						.get(&key)
+						.is_some_and(|last| last.ip == ip && now - last.at < max_age);
+
+					if is_fresh {
+						Ok(Outcome::Unchanged(ip))
+					} else if dry_run {
+						Ok(Outcome::WouldPublish(
+							ip,
+							record.provider.describe_update(ip),
+						))
+					} else {
+						match record.provider.update(ip).await {
+							Ok(res) => {
+								published.insert(key, Published { ip, at: now });
+								changed = true;
+								Ok(Outcome::Published(ip, res))
+							}
+							Err(err) => Err(SyncError::Publish(ip, err)),
+						}
+					}
+				}
+				Err(err) => Err(SyncError::Lookup(family, err.clone())),
+			};
+
+			statuses.push(RecordStatus {
+				record: record.name.clone(),
+				family,
+				result,
+			});
+		}
+	}
+
+	let save_error = match changed {
+		true => state.save(&published).err(),
+		false => None,
+	};
+
+	Ok(SyncReport {
+		statuses,
+		save_error,
+	})
+}
+
+async fn lookup_if_published(
+	records: &[Record],
+	lookup: &dyn IpLookup,
+	family: Family,
+) -> Option<Result<IpAddr, Arc<IpLookupError>>> {
+	if !records
+		.iter()
+		.any(|record| record.families.contains(&family))
+	{
+		return None;
+	}
+
+	Some(lookup_family(lookup, family).await.map_err(Arc::new))
+}
+
+/// Looks up the address of `family`, as an endpoint may respond with either if the host can be
+/// reached by both.
+async fn lookup_family(lookup: &dyn IpLookup, family: Family) -> Result<IpAddr, IpLookupError> {
+	let ip = lookup.lookup().await?;
+
+	if Family::of(ip) != family {
+		return Err(IpLookupError::UnexpectedFamily(ip));
+	}
+
+	Ok(ip)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ip_lookup::FakeIpLookup;
+	use crate::provider::FakeDnsProvider;
+
+	fn record(name: &str, provider: &FakeDnsProvider, families: &[Family]) -> Record {
+		Record {
+			name: name.to_string(),
+			provider: Box::new(provider.clone()),
+			families: families.to_vec(),
+		}
+	}
+
+	fn outcomes(report: SyncReport) -> Vec<Outcome> {
+		assert!(report.save_error.is_none());
+		report
+			.statuses
+			.into_iter()
+			.map(|status| status.result.unwrap())
+			.collect()
+	}
+
+	#[tokio::test]
+	async fn publishes_only_changes() {
+		// Arrange
+		let dir = tempfile::tempdir().unwrap();
+		let state = StateFile::new(dir.path().join("state.json"));
+		let lookup_v4 = FakeIpLookup::new("203.0.113.7");
+		let lookup_v6 = FakeIpLookup::new("2001:db8::7");
+		let provider = FakeDnsProvider::default();
+		let records = [record("home", &provider, &[Family::V4])];
+		let max_age = TimeDelta::days(1);
+		let now = Utc::now();
+		let run = || {
+			sync(
+				&lookup_v4, &lookup_v6, &records, &state, max_age, false, now,
+			)
+		};
+
+		// Act
+		let first = run().await.unwrap();
+		let repeated = run().await.unwrap();
+		lookup_v4.set("203.0.113.8");
+		let changed = run().await.unwrap();
+
+		// Assert
+		let first_ip = "203.0.113.7".parse().unwrap();
+		let second_ip = "203.0.113.8".parse().unwrap();
+		assert_eq!(provider.updates(), [first_ip, second_ip]);
+		assert_eq!(
+			outcomes(first),
+			[Outcome::Published(
+				first_ip,
+				"published 203.0.113.7".to_string()
+			)]
+		);
+		assert_eq!(outcomes(repeated), [Outcome::Unchanged(first_ip)]);
+		assert_eq!(
+			outcomes(changed),
+			[Outcome::Published(
+				second_ip,
+				"published 203.0.113.8".to_string()
+			)]
+		);
+	}
+
+	#[tokio::test]
+	async fn refreshes_after_max_age() {
+		// Arrange
+		let dir = tempfile::tempdir().unwrap();
+		let state = StateFile::new(dir.path().join("state.json"));
+		let lookup_v4 = FakeIpLookup::new("203.0.113.7");
+		let lookup_v6 = FakeIpLookup::new("2001:db8::7");
+		let provider = FakeDnsProvider::default();
+		let records = [record("home", &provider, &[Family::V4])];
+		let max_age = TimeDelta::days(1);
+		let now = Utc::now();
+		let run = |now| {
+			sync(
+				&lookup_v4, &lookup_v6, &records, &state, max_age, false, now,
+			)
+		};
+		run(now).await.unwrap();
+
+		// Act
+		let refreshed = run(now + max_age).await.unwrap();
+
+		// Assert
+		let ip = "203.0.113.7".parse().unwrap();
+		assert_eq!(
+			outcomes(refreshed),
+			[Outcome::Published(ip, "published 203.0.113.7".to_string())]
+		);
+	}
+
+	#[tokio::test]
+	async fn retries_failed_publish() {
+		// Arrange
+		let dir = tempfile::tempdir().unwrap();
+		let state = StateFile::new(dir.path().join("state.json"));
+		let lookup_v4 = FakeIpLookup::new("203.0.113.7");
+		let lookup_v6 = FakeIpLookup::new("2001:db8::7");
+		let provider = FakeDnsProvider::default();
+		let records = [record("home", &provider, &[Family::V4])];
+		let max_age = TimeDelta::days(1);
+		let now = Utc::now();
+		let run = || {
+			sync(
+				&lookup_v4, &lookup_v6, &records, &state, max_age, false, now,
+			)
+		};
+
+		// Act
+		provider.set_reject(true);
+		let failed = run().await.unwrap();
+		provider.set_reject(false);
+		let retried = run().await.unwrap();
+
+		// Assert
+		let ip = "203.0.113.7".parse().unwrap();
+		assert!(matches!(
+			failed.statuses[0].result,
+			Err(SyncError::Publish(..))
+		));
+		assert_eq!(
+			outcomes(retried),
+			[Outcome::Published(ip, "published 203.0.113.7".to_string())]
+		);
+	}
+
+	#[tokio::test]
+	async fn tracks_records_and_families_separately() {
+		// Arrange
+		let dir = tempfile::tempdir().unwrap();
+		let state = StateFile::new(dir.path().join("state.json"));
+		let lookup_v4 = FakeIpLookup::new("203.0.113.7");
+		let lookup_v6 = FakeIpLookup::new("2001:db8::7");
+		let dual_stack = FakeDnsProvider::default();
+		let legacy = FakeDnsProvider::default();
+		let records = [
+			record("home", &dual_stack, &[Family::V4, Family::V6]),
+			record("www", &legacy, &[Family::V4]),
+		];
+		let max_age = TimeDelta::days(1);
+		let now = Utc::now();
+		let run = || {
+			sync(
+				&lookup_v4, &lookup_v6, &records, &state, max_age, false, now,
+			)
+		};
+		run().await.unwrap();
+
+		// Act
+		lookup_v6.set("2001:db8::8");
+		let report = run().await.unwrap();
+
+		// Assert
+		let ip_v4 = "203.0.113.7".parse().unwrap();
+		let first_ip_v6 = "2001:db8::7".parse().unwrap();
+		let second_ip_v6 = "2001:db8::8".parse().unwrap();
+		assert_eq!(dual_stack.updates(), [ip_v4, first_ip_v6, second_ip_v6]);
+		assert_eq!(legacy.updates(), [ip_v4]);
+		let families = report
+			.statuses
+			.iter()
+			.map(|status| (status.record.as_str(), status.family))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			families,
+			[
+				("home", Family::V4),
+				("home", Family::V6),
+				("www", Family::V4)
+			]
+		);
+		assert_eq!(
+			outcomes(report),
+			[
+				Outcome::Unchanged(ip_v4),
+				Outcome::Published(second_ip_v6, "published 2001:db8::8".to_string()),
+				Outcome::Unchanged(ip_v4),
+			]
+		);
+	}
+
+	#[tokio::test]
+	async fn fails_only_records_of_unavailable_family() {
+		// Arrange
+		let dir = tempfile::tempdir().unwrap();
+		let state = StateFile::new(dir.path().join("state.json"));
+		let lookup_v4 = FakeIpLookup::new("203.0.113.7");
+		// an endpoint reachable by both falls back to IPv4 if the host has no IPv6 address
+		let lookup_v6 = FakeIpLookup::new("203.0.113.7");
+		let provider = FakeDnsProvider::default();
+		let records = [record("home", &provider, &[Family::V4, Family::V6])];
+		let max_age = TimeDelta::days(1);
+		let now = Utc::now();
+
+		// Act
+		let statuses = sync(
+			&lookup_v4, &lookup_v6, &records, &state, max_age, false, now,
+		)
+		.await
+		.unwrap()
+		.statuses;
+
+		// Assert
+		assert_eq!(
+			provider.updates(),
+			["203.0.113.7".parse::<IpAddr>().unwrap()]
+		);
+		assert!(statuses[0].result.is_ok());
+		assert!(matches!(
+			statuses[1].result,
+			Err(SyncError::Lookup(Family::V6, _))
+		));
+	}
+
+	#[tokio::test]
+	async fn dry_run_only_describes_updates() {
+		// Arrange
+		let dir = tempfile::tempdir().unwrap();
+		let state = StateFile::new(dir.path().join("state.json"));
+		let lookup_v4 = FakeIpLookup::new("203.0.113.7");
+		let lookup_v6 = FakeIpLookup::new("2001:db8::7");
+		let provider = FakeDnsProvider::default();
+		let records = [record("home", &provider, &[Family::V4])];
+		let max_age = TimeDelta::days(1);
+		let now = Utc::now();
+		let run = || sync(&lookup_v4, &lookup_v6, &records, &state, max_age, true, now);
+
+		// Act
+		let first = run().await.unwrap();
+		let repeated = run().await.unwrap();
+
+		// Assert
+		let ip = "203.0.113.7".parse().unwrap();
+		assert!(provider.updates().is_empty());
+		assert!(state.load().unwrap().is_empty());
+		assert_eq!(
+			outcomes(first),
+			[Outcome::WouldPublish(ip, "publish 203.0.113.7".to_string())]
+		);
+		assert_eq!(
+			outcomes(repeated),
+			[Outcome::WouldPublish(ip, "publish 203.0.113.7".to_string())]
+		);
+	}
+
+	#[tokio::test]
+	async fn reports_statuses_when_state_cannot_be_saved() {
+		// Arrange
+		let dir = tempfile::tempdir().unwrap();
+		let state = StateFile::new(dir.path().join("missing").join("state.json"));
+		let lookup_v4 = FakeIpLookup::new("203.0.113.7");
+		let lookup_v6 = FakeIpLookup::new("2001:db8::7");
+		let provider = FakeDnsProvider::default();
+		let records = [record("home", &provider, &[Family::V4])];
+		let max_age = TimeDelta::days(1);
+		let now = Utc::now();
+
+		// Act
+		let report = sync(
+			&lookup_v4, &lookup_v6, &records, &state, max_age, false, now,
+		)
+		.await
+		.unwrap();
+
+		// Assert
+		let ip = "203.0.113.7".parse().unwrap();
+		assert!(matches!(report.save_error, Some(StateError::Io(..))));
+		assert_eq!(
+			report.statuses[0].result.as_ref().unwrap(),
+			&Outcome::Published(ip, "published 203.0.113.7".to_string())
+		);
+	}
+}